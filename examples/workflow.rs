@@ -12,7 +12,7 @@ async fn main() {
     let stdin = io::stdin(); // We get `Stdin` here.
     stdin.read_line(&mut sid).unwrap();
     sid = sid.trim().to_string();
-    sid = sid.replace(|c: char| c == '"', "");
+    sid = sid.replace('"', "");
     let mut egs = EpicGames::new();
     println!("Using Auth Code: {}", sid);
 
@@ -24,7 +24,7 @@ async fn main() {
     let details = egs.account_details().await;
     println!("Account details: {:?}", details);
     let info = egs
-        .account_ids_details(vec![egs.user_details().account_id.unwrap_or_default()])
+        .account_ids_details(vec![egs.account_id().unwrap_or_default()])
         .await;
     println!("Account info: {:?}", info);
     // let friends = egs.account_friends(true).await;
@@ -38,8 +38,8 @@ async fn main() {
     //                 println!("No assets found");
     //             }
     //             Some(ass) => {
-    //                 println!("Library items: {:?}", ass.results.len());
-    //                 // for asset in ass.results.iter() {
+    //                 println!("Library items: {:?}", ass.len());
+    //                 // for asset in ass.results.iter().filter(|a| a.is_downloadable()) {
     //                 //     for version in asset.project_versions.iter() {
     //                 //         loop {
     //                 //             let manifest = egs.fab_asset_manifest(
@@ -88,13 +88,10 @@ async fn main() {
             for url in man.distribution_point_base_urls.iter() {
                 println!("Trying to get download manifest from {}", url);
                 let dm = egs.fab_download_manifest(man.clone(), url).await;
-                match dm {
-                    Ok(d) => {
-                        println!("Got download manifest from {}", url);
-                        println!("Expected Hash: {}", man.manifest_hash);
-                        println!("Download Hash: {}", d.custom_field("DownloadedManifestHash").unwrap_or_default());
-                    }
-                    Err(_) => {}
+                if let Ok(d) = dm {
+                    println!("Got download manifest from {}", url);
+                    println!("Expected Hash: {}", man.manifest_hash);
+                    println!("Download Hash: {}", d.custom_field("DownloadedManifestHash").unwrap_or_default());
                 }
             }
         }
@@ -102,7 +99,7 @@ async fn main() {
 
     // let code = egs.game_token().await;
     // if let Some(c) = code {
-    //     let authorized_url = format!("https://www.epicgames.com/id/exchange?exchangeCode={}&redirectUrl=https%3A%2F%2Fwww.unrealengine.com%2Fdashboard%3Flang%3Den", c.code);
+    //     let authorized_url = c.exchange_url("https://www.unrealengine.com/dashboard?lang=en");
     //     if webbrowser::open(&authorized_url).is_err() {
     //         println!("Please go to {}", authorized_url)
     //     }
@@ -0,0 +1,940 @@
+use crate::api::error::EpicAPIError;
+use crate::api::types::chunk::Chunk;
+use crate::api::types::download_manifest::{
+    join_manifest_path_checked, DownloadManifest, FileChunkPart, FileManifestList,
+};
+use crate::api::types::install::{FileInstallState, FileStatus, InstallProgress};
+use crate::api::EpicAPI;
+use futures::{StreamExt, TryFutureExt};
+use log::{debug, error, warn};
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::future::Future;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{OnceCell, Semaphore};
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+impl EpicAPI {
+    pub async fn download_chunk(
+        &self,
+        link: &Url,
+        timeout: Option<Duration>,
+    ) -> Result<Chunk, EpicAPIError> {
+        self.download_chunk_with_progress(link, timeout, |_| {})
+            .await
+    }
+
+    pub async fn download_chunk_with_progress(
+        &self,
+        link: &Url,
+        timeout: Option<Duration>,
+        mut progress: impl FnMut(u64),
+    ) -> Result<Chunk, EpicAPIError> {
+        debug!("GET {}", link);
+        let response = self
+            .with_download_timeout(self.client.get(link.clone()), timeout)
+            .send()
+            .await?;
+        if response.status() != reqwest::StatusCode::OK {
+            warn!(
+                "{} result: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+            return Err(EpicAPIError::Unknown);
+        }
+        let mut data = Vec::with_capacity(response.content_length().unwrap_or(0) as usize);
+        let mut stream = response.bytes_stream();
+        while let Some(bytes) = stream.next().await {
+            let bytes = bytes?;
+            data.extend_from_slice(&bytes);
+            progress(bytes.len() as u64);
+        }
+        match Chunk::from_vec(data) {
+            None => {
+                error!("Unable to parse the chunk");
+                Err(EpicAPIError::Unknown)
+            }
+            Some(chunk) => Ok(chunk),
+        }
+    }
+
+    pub async fn fetch_image(&self, url: &Url) -> Result<Vec<u8>, EpicAPIError> {
+        debug!("GET {}", url);
+        let response = self.client.get(url.clone()).send().await?;
+        if response.status() != reqwest::StatusCode::OK {
+            warn!(
+                "{} result: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+            return Err(EpicAPIError::Unknown);
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    async fn download_part(&self, part: FileChunkPart) -> Result<Chunk, EpicAPIError> {
+        match &part.link {
+            None => {
+                error!("No download link for chunk {}", part.guid);
+                Err(EpicAPIError::InvalidParams)
+            }
+            Some(link) => self.download_chunk(link, None).await,
+        }
+    }
+
+    async fn download_part_verified(
+        &self,
+        part: FileChunkPart,
+        expected_sha: Option<&str>,
+    ) -> Result<Chunk, EpicAPIError> {
+        let guid = part.guid.clone();
+        fetch_verified(&guid, expected_sha, || self.download_part(part.clone())).await
+    }
+
+    pub async fn download_file(
+        &self,
+        manifest: &DownloadManifest,
+        file: &FileManifestList,
+        target: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<(), EpicAPIError> {
+        write_file(file, target, cancel, 1, |part| {
+            let expected_sha = manifest.chunk_sha(&part.guid);
+            self.download_part_verified(part, expected_sha)
+                .map_ok(Arc::new)
+        })
+        .await
+    }
+
+    pub async fn install_asset(
+        &self,
+        manifest: &DownloadManifest,
+        target_dir: &Path,
+        resume: bool,
+        cancel: &CancellationToken,
+        progress: impl Fn(InstallProgress),
+    ) -> Result<(), EpicAPIError> {
+        install_files(
+            manifest.files(),
+            target_dir,
+            1,
+            1,
+            DEFAULT_CHUNK_CACHE_CAPACITY,
+            resume,
+            cancel,
+            progress,
+            |part| {
+                let expected_sha = manifest.chunk_sha(&part.guid);
+                self.download_part_verified(part, expected_sha)
+            },
+        )
+        .await
+    }
+
+    pub async fn install_asset_selective(
+        &self,
+        manifest: &DownloadManifest,
+        target_dir: &Path,
+        tags: &[String],
+        resume: bool,
+        cancel: &CancellationToken,
+        progress: impl Fn(InstallProgress),
+    ) -> Result<(), EpicAPIError> {
+        install_files(
+            tagged_files(manifest, tags),
+            target_dir,
+            1,
+            1,
+            DEFAULT_CHUNK_CACHE_CAPACITY,
+            resume,
+            cancel,
+            progress,
+            |part| {
+                let expected_sha = manifest.chunk_sha(&part.guid);
+                self.download_part_verified(part, expected_sha)
+            },
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn install_asset_concurrent(
+        &self,
+        manifest: &DownloadManifest,
+        target_dir: &Path,
+        file_concurrency: usize,
+        chunk_concurrency: usize,
+        cache_capacity: usize,
+        resume: bool,
+        cancel: &CancellationToken,
+        progress: impl Fn(InstallProgress),
+    ) -> Result<(), EpicAPIError> {
+        install_files(
+            manifest.files(),
+            target_dir,
+            file_concurrency,
+            chunk_concurrency,
+            cache_capacity,
+            resume,
+            cancel,
+            progress,
+            |part| {
+                let expected_sha = manifest.chunk_sha(&part.guid);
+                self.download_part_verified(part, expected_sha)
+            },
+        )
+        .await
+    }
+}
+
+/// Get the files of the manifest selected by the install tags, with their download links
+fn tagged_files(manifest: &DownloadManifest, tags: &[String]) -> HashMap<String, FileManifestList> {
+    let selected: HashSet<&str> = manifest
+        .files_with_tags(tags)
+        .into_iter()
+        .map(|file| file.filename.as_str())
+        .collect();
+    manifest
+        .files()
+        .into_iter()
+        .filter(|(filename, _)| selected.contains(filename.as_str()))
+        .collect()
+}
+
+/// Default size of the chunk cache used during installation, in bytes
+pub(crate) const DEFAULT_CHUNK_CACHE_CAPACITY: usize = 256 * 1024 * 1024;
+
+/// Cache of the chunks used while installing, so chunks shared between files are fetched once
+///
+/// Fetched chunks are kept until their total size exceeds the capacity,
+/// then the least recently used ones are dropped. Concurrent requests for the same chunk
+/// wait for a single fetch.
+pub(crate) struct ChunkCache {
+    capacity: usize,
+    inner: Mutex<ChunkCacheInner>,
+}
+
+#[derive(Default)]
+struct ChunkCacheInner {
+    chunks: HashMap<String, Arc<OnceCell<Arc<Chunk>>>>,
+    /// Generation of the last use and size of every fetched chunk
+    entries: HashMap<String, (u64, usize)>,
+    /// Fetched chunks keyed by the generation of their last use, the oldest first
+    order: BTreeMap<u64, String>,
+    generation: u64,
+    size: usize,
+}
+
+impl ChunkCacheInner {
+    fn next_generation(&mut self) -> u64 {
+        self.generation += 1;
+        self.generation
+    }
+}
+
+impl ChunkCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        ChunkCache {
+            capacity,
+            inner: Mutex::new(ChunkCacheInner::default()),
+        }
+    }
+
+    /// Get the chunk from the cache, fetching it when it is not cached
+    pub(crate) async fn get_or_fetch<F, Fut>(
+        &self,
+        guid: &str,
+        fetch: F,
+    ) -> Result<Arc<Chunk>, EpicAPIError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Chunk, EpicAPIError>>,
+    {
+        let cell = {
+            let mut inner = self.inner.lock().unwrap();
+            let generation = inner.next_generation();
+            if let Some((used, _)) = inner.entries.get_mut(guid) {
+                let previous = std::mem::replace(used, generation);
+                inner.order.remove(&previous);
+                inner.order.insert(generation, guid.to_string());
+            }
+            inner.chunks.entry(guid.to_string()).or_default().clone()
+        };
+        let mut fetched = false;
+        let chunk = cell
+            .get_or_try_init(|| {
+                fetched = true;
+                fetch().map_ok(Arc::new)
+            })
+            .await?
+            .clone();
+        if fetched {
+            self.insert(guid, chunk.data.len());
+        }
+        Ok(chunk)
+    }
+
+    fn insert(&self, guid: &str, size: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        let generation = inner.next_generation();
+        if let Some((previous, size)) = inner.entries.insert(guid.to_string(), (generation, size)) {
+            inner.order.remove(&previous);
+            inner.size -= size;
+        }
+        inner.order.insert(generation, guid.to_string());
+        inner.size += size;
+        while inner.size > self.capacity && inner.order.len() > 1 {
+            if let Some((_, evicted)) = inner.order.pop_first() {
+                inner.chunks.remove(&evicted);
+                if let Some((_, size)) = inner.entries.remove(&evicted) {
+                    inner.size -= size;
+                }
+            }
+        }
+    }
+}
+
+fn io_error(e: std::io::Error) -> EpicAPIError {
+    error!("{:?}", e);
+    EpicAPIError::Unknown
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut output, b| {
+        let _ = write!(output, "{b:02x}");
+        output
+    })
+}
+
+/// Fetch a chunk and compare its SHA-1 with the expected one, retrying once on mismatch
+async fn fetch_verified<F, Fut>(
+    guid: &str,
+    expected_sha: Option<&str>,
+    fetch: F,
+) -> Result<Chunk, EpicAPIError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Chunk, EpicAPIError>>,
+{
+    let expected_sha = match expected_sha {
+        None => return fetch().await,
+        Some(sha) => sha,
+    };
+    for attempt in 0..2 {
+        let chunk = fetch().await?;
+        let sha = to_hex(&Sha1::digest(&chunk.data));
+        if sha.eq_ignore_ascii_case(expected_sha) {
+            return Ok(chunk);
+        }
+        warn!(
+            "Chunk {} SHA mismatch on attempt {}, expected {} got {}",
+            guid,
+            attempt + 1,
+            expected_sha,
+            sha
+        );
+    }
+    error!("Chunk {} failed verification", guid);
+    Err(EpicAPIError::Unknown)
+}
+
+/// Compute the hex encoded SHA-1 of the file
+async fn file_sha1(path: &Path) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha1::new();
+    let mut buffer = vec![0; 1024 * 1024];
+    loop {
+        match file.read(&mut buffer).await? {
+            0 => break,
+            read => hasher.update(&buffer[..read]),
+        }
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Check whether the file exists and its SHA-1 matches the hex encoded hash
+pub(crate) async fn file_matches_hash(path: &Path, hash: &str) -> bool {
+    match file_sha1(path).await {
+        Ok(digest) => digest.eq_ignore_ascii_case(hash),
+        Err(e) if e.kind() == ErrorKind::NotFound => false,
+        Err(e) => {
+            warn!("Failed to read {}: {:?}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// Verify every file of the manifest in the install directory, sorted by filename
+///
+/// Fails with `EpicAPIError::InvalidParams` when a file path leaves the install directory.
+pub(crate) async fn verify_installation(
+    manifest: &DownloadManifest,
+    install_dir: &Path,
+) -> Result<Vec<FileStatus>, EpicAPIError> {
+    let mut files: Vec<&FileManifestList> = manifest.file_manifest_list.iter().collect();
+    files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    let mut result = Vec::with_capacity(files.len());
+    for file in files {
+        let filename = &file.filename;
+        let path = join_manifest_path_checked(install_dir, filename)?;
+        let status = match tokio::fs::metadata(&path).await {
+            Err(e) if e.kind() == ErrorKind::NotFound => FileStatus::Missing(filename.clone()),
+            Err(e) => {
+                warn!("Failed to read {}: {:?}", path.display(), e);
+                FileStatus::Unreadable(filename.clone())
+            }
+            Ok(metadata) if metadata.len() as u128 != file.size() => FileStatus::SizeMismatch {
+                filename: filename.clone(),
+                expected: file.size(),
+                actual: metadata.len() as u128,
+            },
+            Ok(_) => match file_sha1(&path).await {
+                Ok(digest) if digest.eq_ignore_ascii_case(&file.file_hash) => {
+                    FileStatus::Ok(filename.clone())
+                }
+                Ok(_) => FileStatus::Corrupt(filename.clone()),
+                Err(e) => {
+                    warn!("Failed to read {}: {:?}", path.display(), e);
+                    FileStatus::Unreadable(filename.clone())
+                }
+            },
+        };
+        debug!("Verified {:?}", status);
+        result.push(status);
+    }
+    Ok(result)
+}
+
+/// Reconstruct a file from its chunk parts, fetching each chunk with `fetch`
+///
+/// Up to `concurrency` chunks are fetched ahead while the parts are written in order.
+/// The cancellation token is checked before and while fetching every chunk.
+pub(crate) async fn write_file<F, Fut>(
+    file: &FileManifestList,
+    target: &Path,
+    cancel: &CancellationToken,
+    concurrency: usize,
+    fetch: F,
+) -> Result<(), EpicAPIError>
+where
+    F: FnMut(FileChunkPart) -> Fut,
+    Fut: Future<Output = Result<Arc<Chunk>, EpicAPIError>>,
+{
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+    }
+    let mut out = tokio::fs::File::create(target).await.map_err(io_error)?;
+    let mut chunks = futures::stream::iter(file.file_chunk_parts.clone())
+        .map(fetch)
+        .buffered(concurrency.max(1));
+    for part in &file.file_chunk_parts {
+        if cancel.is_cancelled() {
+            return Err(EpicAPIError::Cancelled);
+        }
+        let chunk = tokio::select! {
+            _ = cancel.cancelled() => return Err(EpicAPIError::Cancelled),
+            chunk = chunks.next() => match chunk {
+                None => return Err(EpicAPIError::Unknown),
+                Some(chunk) => chunk?,
+            },
+        };
+        let start = part.offset as usize;
+        let end = start + part.size as usize;
+        if end > chunk.data.len() {
+            error!("Chunk {} is shorter than the file part", part.guid);
+            return Err(EpicAPIError::Unknown);
+        }
+        out.write_all(&chunk.data[start..end])
+            .await
+            .map_err(io_error)?;
+    }
+    out.flush().await.map_err(io_error)
+}
+
+/// Reconstruct multiple files in parallel, fetching each chunk with `fetch`
+///
+/// At most `file_concurrency` files are written and `chunk_concurrency` chunks fetched at once.
+/// Chunks are shared between the files through a cache of `cache_capacity` bytes,
+/// so a chunk used by several files is only fetched once while it stays cached.
+/// With `resume` files already present with the expected hash are skipped.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn install_files<F, Fut, P>(
+    files: HashMap<String, FileManifestList>,
+    target_dir: &Path,
+    file_concurrency: usize,
+    chunk_concurrency: usize,
+    cache_capacity: usize,
+    resume: bool,
+    cancel: &CancellationToken,
+    progress: P,
+    fetch: F,
+) -> Result<(), EpicAPIError>
+where
+    F: Fn(FileChunkPart) -> Fut,
+    Fut: Future<Output = Result<Chunk, EpicAPIError>>,
+    P: Fn(InstallProgress),
+{
+    let file_permits = Semaphore::new(file_concurrency.max(1));
+    let chunk_permits = Semaphore::new(chunk_concurrency.max(1));
+    let chunks = ChunkCache::new(cache_capacity);
+    let completed = Mutex::new(InstallProgress {
+        files_total: files.len(),
+        bytes_total: files.values().map(|file| file.size()).sum(),
+        ..Default::default()
+    });
+    let fetch_shared = |part: FileChunkPart| {
+        let (chunks, chunk_permits, fetch) = (&chunks, &chunk_permits, &fetch);
+        async move {
+            let guid = part.guid.clone();
+            chunks
+                .get_or_fetch(&guid, || async {
+                    let _permit = chunk_permits
+                        .acquire()
+                        .await
+                        .map_err(|_| EpicAPIError::Unknown)?;
+                    fetch(part).await
+                })
+                .await
+        }
+    };
+    // Reject paths leaving the target before anything is fetched or written
+    for filename in files.keys() {
+        join_manifest_path_checked(target_dir, filename)?;
+    }
+    let (file_permits, completed, progress, fetch_shared) =
+        (&file_permits, &completed, &progress, &fetch_shared);
+    futures::future::try_join_all(files.iter().map(|(filename, file)| async move {
+        let _permit = file_permits
+            .acquire()
+            .await
+            .map_err(|_| EpicAPIError::Unknown)?;
+        let target = join_manifest_path_checked(target_dir, filename)?;
+        let state = if resume && file_matches_hash(&target, &file.file_hash).await {
+            debug!("{} already present, skipping", filename);
+            FileInstallState::AlreadyPresent
+        } else {
+            write_file(file, &target, cancel, chunk_concurrency, fetch_shared).await?;
+            FileInstallState::Downloaded
+        };
+        let report = {
+            let mut completed = completed.lock().unwrap();
+            completed.filename = filename.clone();
+            completed.state = state;
+            completed.files_completed += 1;
+            completed.bytes_completed += file.size();
+            completed.clone()
+        };
+        progress(report);
+        Ok(())
+    }))
+    .await
+    .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::download::{
+        fetch_verified, install_files, tagged_files, to_hex, verify_installation, write_file,
+        ChunkCache,
+    };
+    use crate::api::error::EpicAPIError;
+    use crate::api::mock::{binary_response, MockServer, STALLED};
+    use crate::api::types::chunk::Chunk;
+    use crate::api::types::download_manifest::{DownloadManifest, FileChunkPart, FileManifestList};
+    use crate::api::types::install::{FileInstallState, FileStatus};
+    use crate::api::{ClientConfig, EpicAPI};
+    use sha1::{Digest, Sha1};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio_util::sync::CancellationToken;
+
+    fn chunk(data: Vec<u8>) -> Chunk {
+        let mut chunk = Chunk::default();
+        chunk.data = data;
+        chunk
+    }
+
+    fn file_with_parts(count: usize) -> FileManifestList {
+        FileManifestList {
+            filename: "file.bin".to_string(),
+            file_hash: "".to_string(),
+            file_chunk_parts: (0..count)
+                .map(|i| FileChunkPart {
+                    guid: format!("{:032x}", i),
+                    link: None,
+                    offset: 1,
+                    size: 2,
+                })
+                .collect(),
+            install_tags: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn write_file_from_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("sub").join("file.bin");
+        let cancel = CancellationToken::new();
+        let result = write_file(
+            &file_with_parts(2),
+            &target,
+            &cancel,
+            2,
+            |part| async move {
+                let n = part.guid.as_bytes()[31];
+                Ok(Arc::new(chunk(vec![0, n, n, 0])))
+            },
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(std::fs::read(&target).unwrap(), b"0011".to_vec());
+    }
+
+    #[tokio::test]
+    async fn cancel_stops_chunk_fetches() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("file.bin");
+        let cancel = CancellationToken::new();
+        let mut fetches = 0;
+        let result = write_file(&file_with_parts(3), &target, &cancel, 1, |_| {
+            fetches += 1;
+            cancel.cancel();
+            async { Ok(Arc::new(chunk(vec![0; 4]))) }
+        })
+        .await;
+        assert!(matches!(result, Err(EpicAPIError::Cancelled)));
+        assert_eq!(fetches, 1);
+    }
+
+    #[tokio::test]
+    async fn install_files_reports_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let cancel = CancellationToken::new();
+        let mut files = HashMap::new();
+        files.insert("a.bin".to_string(), file_with_parts(2));
+        files.insert("b/b.bin".to_string(), file_with_parts(3));
+        let reports = Mutex::new(Vec::new());
+        let result = install_files(
+            files,
+            dir.path(),
+            2,
+            2,
+            1024,
+            false,
+            &cancel,
+            |progress| reports.lock().unwrap().push(progress),
+            |part| async move {
+                let n = part.guid.as_bytes()[31];
+                Ok(chunk(vec![0, n, n, 0]))
+            },
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(std::fs::read(dir.path().join("a.bin")).unwrap(), b"0011");
+        assert_eq!(
+            std::fs::read(dir.path().join("b").join("b.bin")).unwrap(),
+            b"001122"
+        );
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 2);
+        let last = reports.last().unwrap();
+        assert_eq!(last.files_completed, 2);
+        assert_eq!(last.files_total, 2);
+        assert_eq!(last.bytes_completed, 10);
+        assert_eq!(last.bytes_total, 10);
+    }
+
+    #[tokio::test]
+    async fn shared_chunks_fetched_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let cancel = CancellationToken::new();
+        let mut files = HashMap::new();
+        files.insert("a.bin".to_string(), file_with_parts(3));
+        files.insert("b.bin".to_string(), file_with_parts(2));
+        let fetched = Mutex::new(Vec::new());
+        let result = install_files(
+            files,
+            dir.path(),
+            2,
+            4,
+            1024,
+            false,
+            &cancel,
+            |_| {},
+            |part| {
+                fetched.lock().unwrap().push(part.guid.clone());
+                async { Ok(chunk(vec![0; 4])) }
+            },
+        )
+        .await;
+        assert!(result.is_ok());
+        let mut fetched = fetched.into_inner().unwrap();
+        fetched.sort();
+        assert_eq!(
+            fetched,
+            (0..3).map(|i| format!("{:032x}", i)).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn chunk_cache_evicts_least_recently_used() {
+        let cache = ChunkCache::new(8);
+        let fetches = Mutex::new(0);
+        let fetch = || {
+            *fetches.lock().unwrap() += 1;
+            async { Ok(chunk(vec![0; 4])) }
+        };
+        cache.get_or_fetch("a", fetch).await.unwrap();
+        cache.get_or_fetch("b", fetch).await.unwrap();
+        cache.get_or_fetch("a", fetch).await.unwrap();
+        cache.get_or_fetch("c", fetch).await.unwrap();
+        assert_eq!(*fetches.lock().unwrap(), 3);
+        cache.get_or_fetch("a", fetch).await.unwrap();
+        assert_eq!(*fetches.lock().unwrap(), 3);
+        cache.get_or_fetch("b", fetch).await.unwrap();
+        assert_eq!(*fetches.lock().unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn resume_skips_present_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cancel = CancellationToken::new();
+        std::fs::write(dir.path().join("a.bin"), b"0011").unwrap();
+        let mut present = file_with_parts(2);
+        present.file_hash = to_hex(&Sha1::digest(b"0011"));
+        let mut files = HashMap::new();
+        files.insert("a.bin".to_string(), present);
+        let reports = Mutex::new(Vec::new());
+        let fetches = Mutex::new(0);
+        let result = install_files(
+            files,
+            dir.path(),
+            1,
+            1,
+            1024,
+            true,
+            &cancel,
+            |progress| reports.lock().unwrap().push(progress),
+            |_| {
+                *fetches.lock().unwrap() += 1;
+                async { Ok(chunk(vec![0; 4])) }
+            },
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(*fetches.lock().unwrap(), 0);
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].state, FileInstallState::AlreadyPresent);
+        assert_eq!(reports[0].bytes_completed, 4);
+    }
+
+    #[tokio::test]
+    async fn paths_leaving_the_target_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("install");
+        let cancel = CancellationToken::new();
+        for name in ["../escape.bin", "Game\\..\\..\\escape.bin", "/escape.bin"] {
+            let mut files = HashMap::new();
+            files.insert("a.bin".to_string(), file_with_parts(1));
+            files.insert(name.to_string(), file_with_parts(1));
+            let fetches = Mutex::new(0);
+            let result = install_files(
+                files,
+                &target,
+                1,
+                1,
+                1024,
+                false,
+                &cancel,
+                |_| {},
+                |_| {
+                    *fetches.lock().unwrap() += 1;
+                    async { Ok(chunk(vec![0; 4])) }
+                },
+            )
+            .await;
+            assert!(matches!(result, Err(EpicAPIError::InvalidParams)));
+            assert_eq!(*fetches.lock().unwrap(), 0);
+
+            let mut manifest = DownloadManifest::default();
+            let mut file = file_with_parts(1);
+            file.filename = name.to_string();
+            manifest.file_manifest_list.push(file);
+            assert!(matches!(
+                verify_installation(&manifest, &target).await,
+                Err(EpicAPIError::InvalidParams)
+            ));
+        }
+        assert!(!dir.path().join("escape.bin").exists());
+
+        let mut manifest = DownloadManifest::default();
+        let mut file = file_with_parts(1);
+        file.filename = "Game\\Binaries\\game.exe".to_string();
+        manifest.file_manifest_list.push(file);
+        assert_eq!(
+            verify_installation(&manifest, &target).await.unwrap(),
+            vec![FileStatus::Missing("Game\\Binaries\\game.exe".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_installation_statuses() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ok.bin"), b"0011").unwrap();
+        std::fs::write(dir.path().join("corrupt.bin"), b"0012").unwrap();
+        std::fs::write(dir.path().join("short.bin"), b"00").unwrap();
+        let hash = to_hex(&Sha1::digest(b"0011"));
+        let mut manifest = DownloadManifest::default();
+        // A path below a regular file fails with an error other than not found
+        for name in [
+            "ok.bin",
+            "corrupt.bin",
+            "short.bin",
+            "missing.bin",
+            "ok.bin/nested.bin",
+        ] {
+            let mut file = file_with_parts(2);
+            file.filename = name.to_string();
+            file.file_hash = hash.clone();
+            manifest.file_manifest_list.push(file);
+        }
+        let statuses = verify_installation(&manifest, dir.path()).await.unwrap();
+        assert_eq!(
+            statuses,
+            vec![
+                FileStatus::Corrupt("corrupt.bin".to_string()),
+                FileStatus::Missing("missing.bin".to_string()),
+                FileStatus::Ok("ok.bin".to_string()),
+                FileStatus::Unreadable("ok.bin/nested.bin".to_string()),
+                FileStatus::SizeMismatch {
+                    filename: "short.bin".to_string(),
+                    expected: 4,
+                    actual: 2
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn chunk_sha_mismatch_retried_once() {
+        let sha = to_hex(&Sha1::digest(b"good"));
+        let fetches = Mutex::new(0);
+        let result = fetch_verified("aa", Some(&sha), || {
+            let attempt = {
+                let mut fetches = fetches.lock().unwrap();
+                *fetches += 1;
+                *fetches
+            };
+            async move {
+                Ok(chunk(if attempt == 1 {
+                    b"bad".to_vec()
+                } else {
+                    b"good".to_vec()
+                }))
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap().data, b"good");
+        assert_eq!(*fetches.lock().unwrap(), 2);
+
+        let fetches = Mutex::new(0);
+        let result = fetch_verified("aa", Some(&sha), || {
+            *fetches.lock().unwrap() += 1;
+            async { Ok(chunk(b"bad".to_vec())) }
+        })
+        .await;
+        assert!(matches!(result, Err(EpicAPIError::Unknown)));
+        assert_eq!(*fetches.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn selective_install_files() {
+        let mut manifest = DownloadManifest::default();
+        for (name, tags) in [
+            ("base.bin", vec![]),
+            ("hd.bin", vec!["HighRes".to_string()]),
+            ("docs.bin", vec!["Docs".to_string()]),
+        ] {
+            let mut file = file_with_parts(1);
+            file.filename = name.to_string();
+            file.install_tags = tags;
+            manifest.file_manifest_list.push(file);
+        }
+        let mut selected: Vec<String> = tagged_files(&manifest, &["HighRes".to_string()])
+            .into_keys()
+            .collect();
+        selected.sort();
+        assert_eq!(selected, vec!["base.bin", "hd.bin"]);
+        let untagged: Vec<String> = tagged_files(&manifest, &[]).into_keys().collect();
+        assert_eq!(untagged, vec!["base.bin"]);
+    }
+
+    #[tokio::test]
+    async fn chunk_timeout_per_call() {
+        let server = MockServer::start(vec![STALLED.to_string()]).await;
+        let api = EpicAPI::with_config(ClientConfig {
+            download_timeout: Some(Duration::from_secs(60)),
+            ..Default::default()
+        });
+        let result = tokio::time::timeout(
+            Duration::from_secs(10),
+            api.download_chunk(&server.url, Some(Duration::from_millis(100))),
+        )
+        .await
+        .expect("the timeout of the call replaces the configured one");
+        match result {
+            Err(EpicAPIError::Network(e)) => assert!(e.is_timeout()),
+            r => panic!("unexpected {:?}", r.map(|chunk| chunk.data.len())),
+        }
+    }
+
+    #[tokio::test]
+    async fn chunk_progress_sums_to_body_length() {
+        let body = chunk((0..200_000u32).map(|i| (i % 251) as u8).collect()).to_vec();
+        let server = MockServer::start(vec![binary_response(200, &body)]).await;
+        let mut received = Vec::new();
+        let chunk = EpicAPI::new()
+            .download_chunk_with_progress(&server.url, None, |bytes| received.push(bytes))
+            .await
+            .unwrap();
+        assert_eq!(chunk.data.len(), 200_000);
+        assert!(!received.is_empty());
+        assert_eq!(received.iter().sum::<u64>(), body.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn download_file_retries_corrupt_chunk() {
+        let (good, bad) = (vec![1, 2, 3, 4], vec![1, 2, 0, 4]);
+        let server = MockServer::start(vec![
+            binary_response(200, &chunk(bad).to_vec()),
+            binary_response(200, &chunk(good.clone()).to_vec()),
+        ])
+        .await;
+        let mut file = file_with_parts(1);
+        file.file_chunk_parts[0].link = Some(server.url.clone());
+        let mut manifest = DownloadManifest::default();
+        let mut chunk_sha_list = HashMap::new();
+        chunk_sha_list.insert(
+            file.file_chunk_parts[0].guid.clone(),
+            to_hex(&Sha1::digest(&good)),
+        );
+        manifest.chunk_sha_list = Some(chunk_sha_list);
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("file.bin");
+        EpicAPI::new()
+            .download_file(&manifest, &file, &target, &CancellationToken::new())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), vec![2, 3]);
+        assert_eq!(server.requests().len(), 2);
+    }
+}
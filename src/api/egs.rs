@@ -3,53 +3,86 @@ use crate::api::types::asset_info::{AssetInfo, GameToken, OwnershipToken};
 use crate::api::types::asset_manifest::AssetManifest;
 use crate::api::types::download_manifest::DownloadManifest;
 use crate::api::types::epic_asset::EpicAsset;
-use crate::api::types::library::Library;
+use crate::api::types::library::{Library, Record};
+use crate::api::types::offer::Offer;
+use crate::api::types::service_status::ServiceStatus;
 use crate::api::EpicAPI;
+use futures::{stream, Stream, StreamExt};
 use log::{debug, error, warn};
+use reqwest::RequestBuilder;
 use std::borrow::BorrowMut;
-use std::collections::HashMap;
-use std::str::FromStr;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 use url::Url;
 
+/// Maximum number of catalog item ids sent in a single bulk request
+const BULK_ITEMS_BATCH: usize = 50;
+
+/// Country used for catalog requests when none is given
+pub(crate) const DEFAULT_COUNTRY: &str = "us";
+
+/// Locale used for catalog requests when none is given
+pub(crate) const DEFAULT_LOCALE: &str = "en";
+
+/// Maximum number of ownership token requests running at the same time
+const OWNERSHIP_TOKEN_CONCURRENCY: usize = 8;
+
 impl EpicAPI {
-    pub async fn assets(
-        &mut self,
+    pub(crate) fn assets_request(
+        &self,
         platform: Option<String>,
         label: Option<String>,
-    ) -> Result<Vec<EpicAsset>, EpicAPIError> {
+    ) -> RequestBuilder {
         let plat = platform.unwrap_or_else(|| "Windows".to_string());
         let lab = label.unwrap_or_else(|| "Live".to_string());
-        let url = format!("https://launcher-public-service-prod06.ol.epicgames.com/launcher/api/public/assets/{}?label={}", plat, lab);
-        match self
-            .authorized_get_client(Url::parse(&url).unwrap())
-            .send()
-            .await
-        {
-            Ok(response) => {
-                if response.status() == reqwest::StatusCode::OK {
-                    match response.json().await {
-                        Ok(assets) => Ok(assets),
-                        Err(e) => {
-                            error!("{:?}", e);
-                            Err(EpicAPIError::Unknown)
-                        }
-                    }
-                } else {
-                    warn!(
-                        "{} result: {}",
-                        response.status(),
-                        response.text().await.unwrap()
-                    );
-                    Err(EpicAPIError::Unknown)
-                }
-            }
-            Err(e) => {
-                error!("{:?}", e);
-                Err(EpicAPIError::Unknown)
-            }
+        let url = self
+            .launcher_host()
+            .join(&format!(
+                "launcher/api/public/assets/{}?label={}",
+                plat, lab
+            ))
+            .unwrap();
+        self.authorized_get_client(url)
+    }
+
+    fn launcher_host(&self) -> Url {
+        match &self.config.launcher_url {
+            None => Url::parse("https://launcher-public-service-prod06.ol.epicgames.com/").unwrap(),
+            Some(url) => url.clone(),
         }
     }
 
+    pub async fn assets(
+        &self,
+        platform: Option<String>,
+        label: Option<String>,
+    ) -> Result<Vec<EpicAsset>, EpicAPIError> {
+        self.get_json(self.assets_request(platform, label)).await
+    }
+
+    pub async fn assets_if_modified(
+        &self,
+        platform: Option<String>,
+        label: Option<String>,
+    ) -> Result<Option<Vec<EpicAsset>>, EpicAPIError> {
+        self.get_json_if_modified(self.assets_request(platform, label))
+            .await
+    }
+
+    pub async fn assets_all_labels(
+        &self,
+        platform: Option<String>,
+        labels: Vec<String>,
+    ) -> Vec<EpicAsset> {
+        let results = futures::future::join_all(
+            labels
+                .into_iter()
+                .map(|label| self.assets(platform.clone(), Some(label))),
+        )
+        .await;
+        merge_label_assets(results.into_iter().flatten())
+    }
+
     pub async fn asset_manifest(
         &self,
         platform: Option<String>,
@@ -67,61 +100,34 @@ impl EpicAPI {
         if app.is_none() {
             return Err(EpicAPIError::InvalidParams);
         };
-        let url = format!("https://launcher-public-service-prod06.ol.epicgames.com/launcher/api/public/assets/v2/platform/{}/namespace/{}/catalogItem/{}/app/{}/label/{}",
-                          platform.clone().unwrap_or_else(|| "Windows".to_string()), namespace.clone().unwrap(), item_id.clone().unwrap(), app.clone().unwrap(), label.clone().unwrap_or_else(|| "Live".to_string()));
-        match self
-            .authorized_get_client(Url::parse(&url).unwrap())
-            .send()
-            .await
-        {
-            Ok(response) => {
-                if response.status() == reqwest::StatusCode::OK {
-                    match response.json::<AssetManifest>().await {
-                        Ok(mut manifest) => {
-                            manifest.platform = platform;
-                            manifest.label = label;
-                            manifest.namespace = namespace;
-                            manifest.item_id = item_id;
-                            manifest.app = app;
-                            Ok(manifest)
-                        }
-                        Err(e) => {
-                            error!("{:?}", e);
-                            Err(EpicAPIError::Unknown)
-                        }
-                    }
-                } else {
-                    warn!(
-                        "{} result: {}",
-                        response.status(),
-                        response.text().await.unwrap()
-                    );
-                    Err(EpicAPIError::Unknown)
-                }
-            }
-            Err(e) => {
-                error!("{:?}", e);
-                Err(EpicAPIError::Unknown)
-            }
-        }
+        let url = self.launcher_host().join(&format!("launcher/api/public/assets/v2/platform/{}/namespace/{}/catalogItem/{}/app/{}/label/{}",
+                          platform.clone().unwrap_or_else(|| "Windows".to_string()), namespace.clone().unwrap(), item_id.clone().unwrap(), app.clone().unwrap(), label.clone().unwrap_or_else(|| "Live".to_string()))).unwrap();
+        let mut manifest: AssetManifest = self.get_json(self.authorized_get_client(url)).await?;
+        manifest.platform = platform;
+        manifest.label = label;
+        manifest.namespace = namespace;
+        manifest.item_id = item_id;
+        manifest.app = app;
+        Ok(manifest)
     }
 
     pub async fn asset_download_manifests(
         &self,
         asset_manifest: AssetManifest,
+        timeout: Option<Duration>,
     ) -> Vec<DownloadManifest> {
         let base_urls = asset_manifest.url_csv();
         let mut result: Vec<DownloadManifest> = Vec::new();
         for elem in asset_manifest.elements {
             for manifest in elem.manifests {
-                let mut queries: Vec<String> = Vec::new();
                 debug!("{:?}", manifest);
-                for query in manifest.query_params {
-                    queries.push(format!("{}={}", query.name, query.value));
-                }
-                let url = format!("{}?{}", manifest.uri, queries.join("&"));
-                let client = EpicAPI::build_client().build().unwrap();
-                match client.get(Url::from_str(&url).unwrap()).send().await {
+                let url = manifest.signed_url();
+                debug!("GET {}", url);
+                match self
+                    .with_download_timeout(self.client.get(url), timeout)
+                    .send()
+                    .await
+                {
                     Ok(response) => {
                         if response.status() == reqwest::StatusCode::OK {
                             match response.bytes().await {
@@ -200,78 +206,210 @@ impl EpicAPI {
         result
     }
 
+    pub async fn download_manifests_for_all_assets(
+        &self,
+        platform: Option<String>,
+        label: Option<String>,
+        concurrency: usize,
+    ) -> Result<HashMap<String, Vec<DownloadManifest>>, EpicAPIError> {
+        let assets = self.assets(platform.clone(), label.clone()).await?;
+        Ok(stream::iter(assets)
+            .map(|asset| {
+                let (platform, label) = (platform.clone(), label.clone());
+                async move {
+                    let manifest = self
+                        .asset_manifest(
+                            platform,
+                            label,
+                            Some(asset.namespace.clone()),
+                            Some(asset.catalog_item_id.clone()),
+                            Some(asset.app_name.clone()),
+                        )
+                        .await;
+                    match manifest {
+                        Ok(manifest) => Some((
+                            asset.app_name,
+                            self.asset_download_manifests(manifest, None).await,
+                        )),
+                        Err(e) => {
+                            warn!("No asset manifest for {}: {}", asset.app_name, e);
+                            None
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .filter_map(|result| async move { result })
+            .collect()
+            .await)
+    }
+
     pub async fn asset_info(
         &self,
         asset: EpicAsset,
     ) -> Result<HashMap<String, AssetInfo>, EpicAPIError> {
-        let url = format!("https://catalog-public-service-prod06.ol.epicgames.com/catalog/api/shared/namespace/{}/bulk/items?id={}&includeDLCDetails=true&includeMainGameDetails=true&country=us&locale=lc",
-                          asset.namespace, asset.catalog_item_id);
-        match self
-            .authorized_get_client(Url::parse(&url).unwrap())
-            .send()
+        self.asset_info_opts(asset, true, true, DEFAULT_COUNTRY, DEFAULT_LOCALE)
             .await
+    }
+
+    pub async fn asset_info_opts(
+        &self,
+        asset: EpicAsset,
+        include_dlc: bool,
+        include_main_game: bool,
+        country: &str,
+        locale: &str,
+    ) -> Result<HashMap<String, AssetInfo>, EpicAPIError> {
+        self.bulk_asset_info(
+            &asset.namespace,
+            &[asset.catalog_item_id],
+            include_dlc,
+            include_main_game,
+            country,
+            locale,
+        )
+        .await
+    }
+
+    pub async fn bulk_asset_info(
+        &self,
+        namespace: &str,
+        ids: &[String],
+        include_dlc: bool,
+        include_main_game: bool,
+        country: &str,
+        locale: &str,
+    ) -> Result<HashMap<String, AssetInfo>, EpicAPIError> {
+        let host = match &self.config.catalog_url {
+            None => Url::parse("https://catalog-public-service-prod06.ol.epicgames.com/").unwrap(),
+            Some(url) => url.clone(),
+        };
+        let mut url = host
+            .join(&format!(
+                "catalog/api/shared/namespace/{}/bulk/items",
+                namespace
+            ))
+            .unwrap();
         {
-            Ok(response) => {
-                if response.status() == reqwest::StatusCode::OK {
-                    match response.json().await {
-                        Ok(info) => Ok(info),
-                        Err(e) => {
-                            error!("{:?}", e);
-                            Err(EpicAPIError::Unknown)
-                        }
-                    }
-                } else {
-                    warn!(
-                        "{} result: {}",
-                        response.status(),
-                        response.text().await.unwrap()
-                    );
-                    Err(EpicAPIError::Unknown)
-                }
+            let mut query = url.query_pairs_mut();
+            for id in ids {
+                query.append_pair("id", id);
             }
-            Err(e) => {
-                error!("{:?}", e);
-                Err(EpicAPIError::Unknown)
+            query
+                .append_pair("includeDLCDetails", &include_dlc.to_string())
+                .append_pair("includeMainGameDetails", &include_main_game.to_string())
+                .append_pair("country", country)
+                .append_pair("locale", locale);
+        }
+        self.get_json(self.authorized_get_client(url)).await
+    }
+
+    pub async fn catalog_items(
+        &self,
+        namespace: &str,
+        ids: &[String],
+    ) -> HashMap<String, AssetInfo> {
+        let mut result = HashMap::new();
+        for (index, batch) in ids.chunks(BULK_ITEMS_BATCH).enumerate() {
+            match self
+                .bulk_asset_info(
+                    namespace,
+                    batch,
+                    true,
+                    true,
+                    DEFAULT_COUNTRY,
+                    DEFAULT_LOCALE,
+                )
+                .await
+            {
+                Ok(info) => result.extend(info),
+                Err(e) => warn!(
+                    "Catalog items batch {} of {} ids failed: {}",
+                    index + 1,
+                    batch.len(),
+                    e
+                ),
             }
         }
+        result
     }
 
-    pub async fn game_token(&self) -> Result<GameToken, EpicAPIError> {
-        let url =
-            "https://account-public-service-prod03.ol.epicgames.com/account/api/oauth/exchange"
-                .to_string();
-        match self
-            .authorized_get_client(Url::parse(&url).unwrap())
-            .send()
+    pub async fn offer(
+        &self,
+        namespace: &str,
+        offer_id: &str,
+        country: &str,
+    ) -> Result<Offer, EpicAPIError> {
+        let mut url = Url::parse(&format!(
+            "https://catalog-public-service-prod06.ol.epicgames.com/catalog/api/shared/namespace/{}/offers/{}",
+            namespace, offer_id
+        ))
+        .unwrap();
+        url.query_pairs_mut()
+            .append_pair("country", country)
+            .append_pair("locale", DEFAULT_LOCALE);
+        self.get_json(self.authorized_get_client(url)).await
+    }
+
+    pub async fn service_status(&self) -> Result<ServiceStatus, EpicAPIError> {
+        let url = "https://lightswitch-public-service-prod06.ol.epicgames.com/lightswitch/api/service/launcher/status";
+        self.get_json(self.authorized_get_client(Url::parse(url).unwrap()))
             .await
-        {
-            Ok(response) => {
-                if response.status() == reqwest::StatusCode::OK {
-                    match response.json().await {
-                        Ok(token) => Ok(token),
-                        Err(e) => {
-                            error!("{:?}", e);
-                            Err(EpicAPIError::Unknown)
-                        }
-                    }
-                } else {
-                    warn!(
-                        "{} result: {}",
-                        response.status(),
-                        response.text().await.unwrap()
-                    );
-                    Err(EpicAPIError::Unknown)
-                }
+    }
+
+    pub async fn assets_info_concurrent(
+        &self,
+        assets: Vec<EpicAsset>,
+        concurrency: usize,
+    ) -> HashMap<String, AssetInfo> {
+        let mut by_namespace: HashMap<String, Vec<String>> = HashMap::new();
+        for asset in assets {
+            let ids = by_namespace.entry(asset.namespace).or_default();
+            if !ids.contains(&asset.catalog_item_id) {
+                ids.push(asset.catalog_item_id);
             }
-            Err(e) => {
-                error!("{:?}", e);
-                Err(EpicAPIError::Unknown)
+        }
+        let mut batches: Vec<(String, Vec<String>)> = Vec::new();
+        for (namespace, ids) in by_namespace {
+            for chunk in ids.chunks(BULK_ITEMS_BATCH) {
+                batches.push((namespace.clone(), chunk.to_vec()));
             }
         }
+        stream::iter(batches)
+            .map(|(namespace, ids)| async move {
+                self.bulk_asset_info(
+                    &namespace,
+                    &ids,
+                    true,
+                    true,
+                    DEFAULT_COUNTRY,
+                    DEFAULT_LOCALE,
+                )
+                .await
+            })
+            .buffer_unordered(concurrency.max(1))
+            .fold(HashMap::new(), |mut result, batch| async move {
+                if let Ok(info) = batch {
+                    result.extend(info);
+                }
+                result
+            })
+            .await
+    }
+
+    pub async fn game_token(&self) -> Result<GameToken, EpicAPIError> {
+        let url =
+            "https://account-public-service-prod03.ol.epicgames.com/account/api/oauth/exchange"
+                .to_string();
+        let mut token: GameToken = self
+            .get_json(self.authorized_get_client(Url::parse(&url).unwrap()))
+            .await?;
+        token.fetched_at = Some(chrono::Utc::now());
+        Ok(token)
     }
 
     pub async fn ownership_token(&self, asset: EpicAsset) -> Result<OwnershipToken, EpicAPIError> {
-        let url = match &self.user_data.account_id {
+        let url = match self.user_data().account_id {
             None => {
                 return Err(EpicAPIError::InvalidCredentials);
             }
@@ -280,38 +418,32 @@ impl EpicAPI {
                         id)
             }
         };
-        match self
+        let response = self
             .authorized_post_client(Url::parse(&url).unwrap())
             .form(&[(
                 "nsCatalogItemId".to_string(),
                 format!("{}:{}", asset.namespace, asset.catalog_item_id),
             )])
             .send()
-            .await
-        {
-            Ok(response) => {
-                if response.status() == reqwest::StatusCode::OK {
-                    match response.json().await {
-                        Ok(token) => Ok(token),
-                        Err(e) => {
-                            error!("{:?}", e);
-                            Err(EpicAPIError::Unknown)
-                        }
-                    }
-                } else {
-                    warn!(
-                        "{} result: {}",
-                        response.status(),
-                        response.text().await.unwrap()
-                    );
-                    Err(EpicAPIError::Unknown)
+            .await?;
+        EpicAPI::json_response(response).await
+    }
+
+    pub async fn ownership_tokens(&self, assets: Vec<EpicAsset>) -> HashMap<String, String> {
+        // The endpoint only takes a single nsCatalogItemId, so issue the requests concurrently
+        stream::iter(assets)
+            .map(|asset| async move {
+                let id = asset.catalog_item_id.clone();
+                (id, self.ownership_token(asset).await)
+            })
+            .buffer_unordered(OWNERSHIP_TOKEN_CONCURRENCY)
+            .fold(HashMap::new(), |mut result, (id, token)| async move {
+                if let Ok(token) = token {
+                    result.insert(id, token.token);
                 }
-            }
-            Err(e) => {
-                error!("{:?}", e);
-                Err(EpicAPIError::Unknown)
-            }
-        }
+                result
+            })
+            .await
     }
 
     pub async fn library_items(&mut self, include_metadata: bool) -> Result<Library, EpicAPIError> {
@@ -321,59 +453,299 @@ impl EpicAPI {
         };
         let mut cursor: Option<String> = None;
         loop {
-            let url = match &cursor {
-                None => {
-                    format!("https://library-service.live.use1a.on.epicgames.com/library/api/public/items?includeMetadata={}", include_metadata)
-                }
-                Some(c) => {
-                    format!("https://library-service.live.use1a.on.epicgames.com/library/api/public/items?includeMetadata={}&cursor={}", include_metadata, c)
-                }
-            };
-
-            match self
-                .authorized_get_client(Url::parse(&url).unwrap())
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status() == reqwest::StatusCode::OK {
-                        match response.json::<Library>().await {
-                            Ok(mut records) => {
-                                library.records.append(records.records.borrow_mut());
-                                match records.response_metadata {
-                                    None => {
-                                        break;
-                                    }
-                                    Some(meta) => match meta.next_cursor {
-                                        None => {
-                                            break;
-                                        }
-                                        Some(curs) => {
-                                            cursor = Some(curs);
-                                        }
-                                    },
-                                }
-                            }
-                            Err(e) => {
-                                error!("{:?}", e);
-                            }
-                        }
-                    } else {
-                        warn!(
-                            "{} result: {}",
-                            response.status(),
-                            response.text().await.unwrap()
-                        );
-                    }
-                }
-                Err(e) => {
-                    error!("{:?}", e);
-                }
-            };
+            let mut page = self.library_items_page(include_metadata, cursor).await?;
+            library.records.append(page.records.borrow_mut());
+            cursor = page.response_metadata.and_then(|meta| meta.next_cursor);
             if cursor.is_none() {
                 break;
             }
         }
         Ok(library)
     }
+
+    pub(crate) fn library_items_request(
+        &self,
+        include_metadata: bool,
+        cursor: Option<String>,
+    ) -> RequestBuilder {
+        let mut url = Url::parse(
+            "https://library-service.live.use1a.on.epicgames.com/library/api/public/items",
+        )
+        .unwrap();
+        url.query_pairs_mut()
+            .append_pair("includeMetadata", &include_metadata.to_string());
+        if let Some(cursor) = cursor {
+            url.query_pairs_mut().append_pair("cursor", &cursor);
+        }
+        self.authorized_get_client(url)
+    }
+
+    pub async fn library_items_page(
+        &self,
+        include_metadata: bool,
+        cursor: Option<String>,
+    ) -> Result<Library, EpicAPIError> {
+        self.get_json(self.library_items_request(include_metadata, cursor))
+            .await
+    }
+
+    pub fn library_items_stream(
+        &self,
+        include_metadata: bool,
+    ) -> impl Stream<Item = Result<Record, EpicAPIError>> + '_ {
+        // The state is the cursor of the next page to fetch, None once there are no more pages
+        stream::unfold(
+            Some(None),
+            move |cursor: Option<Option<String>>| async move {
+                let cursor = cursor?;
+                match self.library_items_page(include_metadata, cursor).await {
+                    Ok(page) => {
+                        let next = page
+                            .response_metadata
+                            .and_then(|meta| meta.next_cursor)
+                            .map(Some);
+                        Some((
+                            stream::iter(page.records.into_iter().map(Ok)).left_stream(),
+                            next,
+                        ))
+                    }
+                    Err(e) => Some((stream::once(async { Err(e) }).right_stream(), None)),
+                }
+            },
+        )
+        .flatten()
+    }
+}
+
+/// Merge the asset lists of several labels, keeping the first of the same build of an item
+fn merge_label_assets(lists: impl IntoIterator<Item = Vec<EpicAsset>>) -> Vec<EpicAsset> {
+    let mut seen = HashSet::new();
+    let mut assets = Vec::new();
+    for list in lists {
+        for asset in list {
+            if seen.insert((
+                asset.namespace.clone(),
+                asset.catalog_item_id.clone(),
+                asset.build_version.clone(),
+            )) {
+                assets.push(asset);
+            }
+        }
+    }
+    assets
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::egs::merge_label_assets;
+    use crate::api::mock::{response, MockServer, STALLED};
+    use crate::api::types::asset_manifest::{AssetManifest, Element, Manifest, QueryParam};
+    use crate::api::types::epic_asset::EpicAsset;
+    use crate::api::{ClientConfig, EpicAPI};
+    use std::time::Duration;
+    use url::Url;
+
+    #[tokio::test]
+    async fn manifest_request_encodes_query_params() {
+        let server = MockServer::start(vec![response(404, &[], "")]).await;
+        let asset_manifest = AssetManifest {
+            elements: vec![Element {
+                manifests: vec![Manifest {
+                    uri: server.url.join("Builds/app.manifest").unwrap(),
+                    query_params: vec![QueryParam {
+                        name: "f_token".to_string(),
+                        value: "exp=1&sig=a+b".to_string(),
+                    }],
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let api = EpicAPI::new();
+        assert!(api
+            .asset_download_manifests(asset_manifest, None)
+            .await
+            .is_empty());
+        let requests = server.requests();
+        assert!(requests[0]
+            .starts_with("GET /Builds/app.manifest?f_token=exp%3D1%26sig%3Da%2Bb HTTP/1.1"));
+    }
+
+    #[test]
+    fn label_assets_deduplicated() {
+        let asset = |catalog_item_id: &str, build_version: &str, label_name: &str| EpicAsset {
+            namespace: "ns".to_string(),
+            catalog_item_id: catalog_item_id.to_string(),
+            build_version: build_version.to_string(),
+            label_name: label_name.to_string(),
+            ..Default::default()
+        };
+        let live = vec![asset("a", "1.0", "Live"), asset("b", "1.0", "Live")];
+        let beta = vec![
+            asset("a", "1.0", "Beta"),
+            asset("a", "1.1", "Beta"),
+            asset("c", "1.0", "Beta"),
+        ];
+        let merged = merge_label_assets(vec![live, beta]);
+        let merged: Vec<(&str, &str, &str)> = merged
+            .iter()
+            .map(|a| {
+                (
+                    a.catalog_item_id.as_str(),
+                    a.build_version.as_str(),
+                    a.label_name.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            merged,
+            vec![
+                ("a", "1.0", "Live"),
+                ("b", "1.0", "Live"),
+                ("a", "1.1", "Beta"),
+                ("c", "1.0", "Beta"),
+            ]
+        );
+        assert!(merge_label_assets(Vec::new()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn manifest_timeout_per_call() {
+        let server = MockServer::start(vec![STALLED.to_string()]).await;
+        let asset_manifest = AssetManifest {
+            elements: vec![Element {
+                manifests: vec![Manifest {
+                    uri: server.url.join("Builds/app.manifest").unwrap(),
+                    query_params: Vec::new(),
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let api = EpicAPI::with_config(ClientConfig {
+            download_timeout: Some(Duration::from_secs(60)),
+            ..Default::default()
+        });
+        let manifests = tokio::time::timeout(
+            Duration::from_secs(10),
+            api.asset_download_manifests(asset_manifest, Some(Duration::from_millis(100))),
+        )
+        .await
+        .expect("the timeout of the call replaces the configured one");
+        assert!(manifests.is_empty());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn catalog_items_batched() {
+        let item = |id: &str| {
+            format!(
+                r#"{{"{0}":{{"id":"{0}","namespace":"ns","unsearchable":false}}}}"#,
+                id
+            )
+        };
+        let server = MockServer::start(vec![
+            response(200, &[], &item("id0")),
+            response(500, &[], ""),
+            response(200, &[], &item("id100")),
+        ])
+        .await;
+        let api = EpicAPI::with_config(ClientConfig {
+            catalog_url: Some(server.url.clone()),
+            ..Default::default()
+        });
+        let ids: Vec<String> = (0..120).map(|i| format!("id{}", i)).collect();
+        let items = api.catalog_items("ns", &ids).await;
+        let mut found: Vec<&String> = items.keys().collect();
+        found.sort();
+        assert_eq!(found, vec!["id0", "id100"]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        let sent = |request: &str| {
+            let target = request.split_whitespace().nth(1).unwrap();
+            Url::parse("http://localhost")
+                .unwrap()
+                .join(target)
+                .unwrap()
+                .query_pairs()
+                .filter(|(name, _)| name == "id")
+                .map(|(_, value)| value.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(requests[0]
+            .starts_with("GET /catalog/api/shared/namespace/ns/bulk/items?id=id0&id=id1&"));
+        assert_eq!(sent(&requests[0]), ids[..50].to_vec());
+        assert_eq!(sent(&requests[1]), ids[50..100].to_vec());
+        assert_eq!(sent(&requests[2]), ids[100..].to_vec());
+    }
+
+    #[tokio::test]
+    async fn download_manifests_for_all_assets() {
+        let download_manifest = r#"{"ManifestFileVersion":"013000000000","bIsFileData":false,"AppID":"000000000000","AppNameString":"First","BuildVersionString":"1.0","LaunchExeString":"","LaunchCommand":"","PrereqName":"","PrereqPath":"","PrereqArgs":"","FileManifestList":[],"ChunkHashList":{},"DataGroupList":{},"ChunkFilesizeList":{}}"#;
+        let cdn = MockServer::start(vec![response(200, &[], download_manifest)]).await;
+        let asset = |app: &str| {
+            format!(
+                r#"{{"appName":"{0}","labelName":"Live","buildVersion":"1.0","catalogItemId":"{0}Item","namespace":"ns","assetId":"{0}"}}"#,
+                app
+            )
+        };
+        let asset_manifest = format!(
+            r#"{{"elements":[{{"appName":"First","labelName":"Live","buildVersion":"1.0","hash":"00","manifests":[{{"uri":"{}","queryParams":[]}}]}}]}}"#,
+            cdn.url.join("Builds/First.manifest").unwrap()
+        );
+        let server = MockServer::start(vec![
+            response(
+                200,
+                &[],
+                &format!("[{},{}]", asset("First"), asset("Second")),
+            ),
+            response(200, &[], &asset_manifest),
+            response(500, &[], ""),
+        ])
+        .await;
+        let api = EpicAPI::with_config(ClientConfig {
+            launcher_url: Some(server.url.clone()),
+            retry_attempts: Some(1),
+            ..Default::default()
+        });
+        let manifests = api
+            .download_manifests_for_all_assets(None, None, 1)
+            .await
+            .unwrap();
+        assert_eq!(manifests.len(), 1);
+        let first = &manifests["First"];
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].app_name_string, "First");
+        assert_eq!(
+            first[0].custom_field("CatalogItemId"),
+            Some("FirstItem".to_string())
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].starts_with("GET /launcher/api/public/assets/Windows?label=Live "));
+        assert!(requests[1].starts_with(
+            "GET /launcher/api/public/assets/v2/platform/Windows/namespace/ns/catalogItem/FirstItem/app/First/label/Live "
+        ));
+        assert!(requests[2].contains("/catalogItem/SecondItem/app/Second/"));
+        let requests = cdn.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /Builds/First.manifest "));
+    }
+
+    #[test]
+    fn library_request_encodes_cursor() {
+        let request = EpicAPI::new()
+            .library_items_request(true, Some("a+b/c=&d".to_string()))
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().query(),
+            Some("includeMetadata=true&cursor=a%2Bb%2Fc%3D%26d")
+        );
+        let request = EpicAPI::new()
+            .library_items_request(false, None)
+            .build()
+            .unwrap();
+        assert_eq!(request.url().query(), Some("includeMetadata=false"));
+    }
 }
@@ -16,6 +16,42 @@ pub enum EpicAPIError {
     Server,
     /// FAB Timeout
     FabTimeout,
+    /// Operation was cancelled
+    Cancelled,
+    /// Network error, the request could not be sent or the response not read
+    Network(reqwest::Error),
+    /// Response could not be decoded
+    Decode(serde_json::Error),
+}
+
+impl EpicAPIError {
+    /// Check whether the operation may succeed when retried later
+    ///
+    /// True for server errors, the temporary FAB throttling and network timeouts or failed connections.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            EpicAPIError::Server | EpicAPIError::FabTimeout => true,
+            EpicAPIError::Network(e) => e.is_timeout() || e.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Check whether the user has to log in again before retrying
+    pub fn needs_reauth(&self) -> bool {
+        matches!(self, EpicAPIError::InvalidCredentials)
+    }
+}
+
+impl From<reqwest::Error> for EpicAPIError {
+    fn from(error: reqwest::Error) -> Self {
+        EpicAPIError::Network(error)
+    }
+}
+
+impl From<serde_json::Error> for EpicAPIError {
+    fn from(error: serde_json::Error) -> Self {
+        EpicAPIError::Decode(error)
+    }
 }
 
 impl fmt::Display for EpicAPIError {
@@ -39,6 +75,15 @@ impl fmt::Display for EpicAPIError {
             EpicAPIError::FabTimeout => {
                 write!(f, "Fab Timeout Error")
             }
+            EpicAPIError::Cancelled => {
+                write!(f, "Operation Cancelled")
+            }
+            EpicAPIError::Network(e) => {
+                write!(f, "Network Error: {}", e)
+            }
+            EpicAPIError::Decode(e) => {
+                write!(f, "Decode Error: {}", e)
+            }
         }
     }
 }
@@ -52,6 +97,51 @@ impl Error for EpicAPIError {
             EpicAPIError::APIError(_) => "API Error",
             EpicAPIError::InvalidParams => "Invalid Input Parameters",
             EpicAPIError::FabTimeout => "Fab Timeout Error",
+            EpicAPIError::Cancelled => "Operation Cancelled",
+            EpicAPIError::Network(_) => "Network Error",
+            EpicAPIError::Decode(_) => "Decode Error",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EpicAPIError::Network(e) => Some(e),
+            EpicAPIError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::error::EpicAPIError;
+    use std::error::Error;
+
+    #[test]
+    fn classification() {
+        assert!(EpicAPIError::Server.is_retryable());
+        assert!(EpicAPIError::FabTimeout.is_retryable());
+        assert!(!EpicAPIError::InvalidCredentials.is_retryable());
+        assert!(EpicAPIError::InvalidCredentials.needs_reauth());
+        for error in [
+            EpicAPIError::APIError("Not owned".to_string()),
+            EpicAPIError::Unknown,
+            EpicAPIError::InvalidParams,
+            EpicAPIError::Cancelled,
+        ] {
+            assert!(!error.is_retryable());
+            assert!(!error.needs_reauth());
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn source_chain() {
+        let decode = serde_json::from_str::<u32>("not a number").unwrap_err();
+        let message = decode.to_string();
+        let error = EpicAPIError::from(decode);
+        assert_eq!(error.source().unwrap().to_string(), message);
+        assert_eq!(error.to_string(), format!("Decode Error: {}", message));
+        assert!(!error.is_retryable());
+        assert!(EpicAPIError::Unknown.source().is_none());
+    }
+}
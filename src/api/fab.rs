@@ -1,14 +1,121 @@
+use crate::api::egs::DEFAULT_LOCALE;
 use crate::api::error::EpicAPIError;
 use crate::api::types::download_manifest::DownloadManifest;
-use crate::api::types::fab_asset_manifest::DownloadInfo;
-use crate::api::types::fab_library::FabLibrary;
+use crate::api::types::fab_asset_manifest::{DistributionPoint, DownloadInfo, FabAssetManifest};
+use crate::api::types::fab_library::{FabAsset, FabLibrary};
 use crate::api::EpicAPI;
-use log::{debug, error, warn};
+use futures::{stream, Stream, StreamExt};
+use log::{debug, error, info, warn};
+use reqwest::header::ACCEPT_LANGUAGE;
+use reqwest::RequestBuilder;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
 use std::borrow::BorrowMut;
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::time::Duration;
 use url::Url;
 
+/// Delay between attempts when the FAB manifest endpoint throttles
+const FAB_RETRY_DELAY: Duration = Duration::from_millis(1000);
+
+/// Page visited to establish the FAB session and CSRF cookies
+const FAB_SESSION_URL: &str = "https://www.fab.com/";
+
+/// Distribution target of the library requests when none is given, the Unreal Engine library
+pub(crate) const DEFAULT_FAB_TARGET: &str = "ue";
+
+/// Largest page size the FAB library endpoint accepts
+pub(crate) const FAB_MAX_PAGE_SIZE: u32 = 100;
+
+/// Settings of the FAB library requests
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FabLibraryOptions<'a> {
+    /// Sent as the `Accept-Language` header
+    pub(crate) locale: &'a str,
+    /// Library path segment, e.g. `ue`
+    pub(crate) target: &'a str,
+    /// Assets per page, between 1 and `FAB_MAX_PAGE_SIZE`
+    pub(crate) count: u32,
+}
+
+impl FabLibraryOptions<'_> {
+    fn validate(&self) -> Result<(), EpicAPIError> {
+        if self.count == 0 || self.count > FAB_MAX_PAGE_SIZE {
+            error!(
+                "Invalid FAB page size {}, expected 1 to {}",
+                self.count, FAB_MAX_PAGE_SIZE
+            );
+            return Err(EpicAPIError::InvalidParams);
+        }
+        Ok(())
+    }
+}
+
+impl Default for FabLibraryOptions<'_> {
+    fn default() -> Self {
+        FabLibraryOptions {
+            locale: DEFAULT_LOCALE,
+            target: DEFAULT_FAB_TARGET,
+            count: FAB_MAX_PAGE_SIZE,
+        }
+    }
+}
+
 impl EpicAPI {
+    /// Establish the FAB session cookies before the first FAB API call
+    ///
+    /// The FAB API expects the session and CSRF cookies set by the website, so the first call
+    /// visits it and the cookies are kept in the shared cookie store for all following calls.
+    /// A failed visit is logged and retried with the next call.
+    pub(crate) async fn ensure_fab_session(&self) {
+        self.ensure_fab_session_at(self.fab_host().as_str()).await
+    }
+
+    fn fab_host(&self) -> Url {
+        match &self.config.fab_url {
+            None => Url::parse(FAB_SESSION_URL).unwrap(),
+            Some(url) => url.clone(),
+        }
+    }
+
+    async fn ensure_fab_session_at(&self, url: &str) {
+        let result = self
+            .fab_session
+            .get_or_try_init(|| async {
+                debug!("GET {}", url);
+                match self.client.get(url).send().await {
+                    Ok(response) if response.status().is_success() => Ok(()),
+                    Ok(response) => Err(format!("{}", response.status())),
+                    Err(e) => Err(format!("{:?}", e)),
+                }
+            })
+            .await;
+        if let Err(e) = result {
+            warn!("Unable to establish the FAB session: {}", e);
+        }
+    }
+
+    pub(crate) fn fab_asset_manifest_request(
+        &self,
+        artifact_id: &str,
+        namespace: &str,
+        asset_id: &str,
+        platform: Option<&str>,
+    ) -> RequestBuilder {
+        let url = self
+            .fab_host()
+            .join(&format!("e/artifacts/{}/manifest", artifact_id))
+            .unwrap();
+        let body = serde_json::json!({
+            "item_id": asset_id,
+            "namespace": namespace,
+            "platform": platform.unwrap_or("Windows"),
+        });
+        debug!("Request body: {}", body);
+        self.authorized_post_client(url).json(&body)
+    }
+
     pub async fn fab_asset_manifest(
         &self,
         artifact_id: &str,
@@ -16,46 +123,69 @@ impl EpicAPI {
         asset_id: &str,
         platform: Option<&str>,
     ) -> Result<Vec<DownloadInfo>, EpicAPIError> {
-        let url = format!("https://www.fab.com/e/artifacts/{}/manifest", artifact_id);
-        match self
-            .authorized_post_client(Url::parse(&url).unwrap())
-            .json(&serde_json::json!({
-                "item_id": asset_id,
-                "namespace": namespace,
-                "platform": platform.unwrap_or("Windows"),
-            }))
+        self.ensure_fab_session().await;
+        let response = self
+            .fab_asset_manifest_request(artifact_id, namespace, asset_id, platform)
             .send()
-            .await
-        {
-            Ok(response) => {
-                if response.status() == reqwest::StatusCode::OK {
-                    let text = response.text().await.unwrap();
-                    match serde_json::from_str::<
-                        crate::api::types::fab_asset_manifest::FabAssetManifest,
-                    >(&text)
-                    {
-                        Ok(manifest) => Ok(manifest.download_info),
-                        Err(e) => {
-                            error!("{:?}", e);
-                            debug!("{}", text);
-                            Err(EpicAPIError::Unknown)
-                        }
-                    }
-                } else if response.status() == reqwest::StatusCode::FORBIDDEN {
-                    Err(EpicAPIError::FabTimeout)
-                } else {
-                    debug!("{:?}", response.headers());
-                    warn!(
-                        "{} result: {}",
-                        response.status(),
-                        response.text().await.unwrap()
-                    );
-                    Err(EpicAPIError::Unknown)
+            .await?;
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            return Err(fab_forbidden_error(
+                &response.text().await.unwrap_or_default(),
+            ));
+        }
+        let manifest: FabAssetManifest = EpicAPI::json_response(response).await?;
+        Ok(manifest.download_info)
+    }
+
+    pub async fn fab_asset_manifest_all_platforms(
+        &self,
+        artifact_id: &str,
+        namespace: &str,
+        asset_id: &str,
+        platforms: &[&str],
+    ) -> HashMap<String, Vec<DownloadInfo>> {
+        let results = futures::future::join_all(platforms.iter().map(|platform| async move {
+            let result = self
+                .fab_asset_manifest(artifact_id, namespace, asset_id, Some(platform))
+                .await;
+            (platform.to_string(), result)
+        }))
+        .await;
+        let mut manifests = HashMap::new();
+        for (platform, result) in results {
+            match result {
+                Ok(download_info) => {
+                    manifests.insert(platform, download_info);
                 }
+                Err(e) => warn!("Fab manifest for {} failed: {}", platform, e),
             }
-            Err(e) => {
-                error!("{:?}", e);
-                Err(EpicAPIError::Unknown)
+        }
+        manifests
+    }
+
+    pub async fn fab_asset_manifest_with_attempts(
+        &self,
+        artifact_id: &str,
+        namespace: &str,
+        asset_id: &str,
+        platform: Option<&str>,
+        max_attempts: u32,
+    ) -> Result<Vec<DownloadInfo>, EpicAPIError> {
+        let mut attempt = 1;
+        loop {
+            match self
+                .fab_asset_manifest(artifact_id, namespace, asset_id, platform)
+                .await
+            {
+                Err(EpicAPIError::FabTimeout) if attempt < max_attempts => {
+                    debug!(
+                        "Fab manifest throttled, attempt {}/{}",
+                        attempt, max_attempts
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(FAB_RETRY_DELAY).await;
+                }
+                result => return result,
             }
         }
     }
@@ -71,104 +201,524 @@ impl EpicAPI {
                 Err(EpicAPIError::Unknown)
             }
             Some(point) => {
-                if point.signature_expiration < time::OffsetDateTime::now_utc() {
-                    error!("Expired signature");
-                    Err(EpicAPIError::Unknown)
-                } else {
-                    let client = EpicAPI::build_client().build().unwrap();
-                    match client
-                        .get(Url::from_str(&point.manifest_url).unwrap())
-                        .send()
-                        .await
-                    {
-                        Ok(response) => {
-                            if response.status() == reqwest::StatusCode::OK {
-                                match response.bytes().await {
-                                    Ok(data) => match DownloadManifest::parse(data.to_vec()) {
-                                        None => {
-                                            error!("Unable to parse the Download Manifest");
-                                            Err(EpicAPIError::Unknown)
-                                        }
-                                        Some(man) => Ok(man),
-                                    },
-                                    Err(_) => Err(EpicAPIError::Unknown),
-                                }
-                            } else {
-                                warn!(
-                                    "{} result: {}",
-                                    response.status(),
-                                    response.text().await.unwrap()
-                                );
-                                Err(EpicAPIError::Unknown)
-                            }
-                        }
-                        Err(_) => Err(EpicAPIError::Unknown),
-                    }
+                self.fab_download_manifest_from_point(point, &download_info)
+                    .await
+            }
+        }
+    }
+
+    pub async fn fab_download_manifest_auto(
+        &self,
+        download_info: DownloadInfo,
+    ) -> Result<(DownloadManifest, DistributionPoint), EpicAPIError> {
+        match download_info
+            .distribution_points
+            .iter()
+            .max_by_key(|point| point.signature_expiration)
+        {
+            None => {
+                error!("No distribution points for {}", download_info.artifact_id);
+                Err(EpicAPIError::Unknown)
+            }
+            Some(point) => {
+                let manifest = self
+                    .fab_download_manifest_from_point(point, &download_info)
+                    .await?;
+                Ok((manifest, point.clone()))
+            }
+        }
+    }
+
+    pub async fn fab_download_manifest_with_fallback(
+        &self,
+        download_info: DownloadInfo,
+    ) -> Result<(DownloadManifest, DistributionPoint), EpicAPIError> {
+        let now = time::OffsetDateTime::now_utc();
+        let mut failures: Vec<String> = Vec::new();
+        for point in download_info
+            .distribution_points
+            .iter()
+            .filter(|point| point.signature_expiration >= now)
+        {
+            match self
+                .fab_download_manifest_from_point(point, &download_info)
+                .await
+            {
+                Ok(manifest) => return Ok((manifest, point.clone())),
+                Err(e) => {
+                    warn!("Distribution point {} failed: {}", point.manifest_url, e);
+                    failures.push(format!("{}: {}", point.manifest_url, e));
                 }
             }
         }
+        if failures.is_empty() {
+            error!(
+                "No valid distribution points for {}",
+                download_info.artifact_id
+            );
+            Err(EpicAPIError::APIError(
+                "No distribution points with a valid signature".to_string(),
+            ))
+        } else {
+            Err(EpicAPIError::APIError(format!(
+                "All distribution points failed: {}",
+                failures.join("; ")
+            )))
+        }
+    }
+
+    /// Download the manifest from the distribution point
+    ///
+    /// The downloaded bytes are compared with the manifest hash of the download, when it has one.
+    async fn fab_download_manifest_from_point(
+        &self,
+        point: &DistributionPoint,
+        download_info: &DownloadInfo,
+    ) -> Result<DownloadManifest, EpicAPIError> {
+        let url = match Url::from_str(&point.manifest_url) {
+            Ok(url) => url,
+            Err(e) => {
+                error!("Invalid manifest url {}: {:?}", point.manifest_url, e);
+                return Err(EpicAPIError::Unknown);
+            }
+        };
+        if point.signature_expiration < time::OffsetDateTime::now_utc() {
+            error!(
+                "Rejecting distribution point {}, signature expired {}",
+                url.host_str().unwrap_or_default(),
+                point.signature_expiration
+            );
+            return Err(EpicAPIError::Unknown);
+        }
+        info!(
+            "Using distribution point {} (signature expires {})",
+            url.host_str().unwrap_or_default(),
+            point.signature_expiration
+        );
+        debug!("GET {}", point.manifest_url);
+        let response = self
+            .with_download_timeout(self.client.get(url), None)
+            .send()
+            .await?;
+        if response.status() != reqwest::StatusCode::OK {
+            warn!(
+                "{} result: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+            return Err(EpicAPIError::Unknown);
+        }
+        let data = response.bytes().await?;
+        match download_info.manifest_hash_bytes() {
+            None => debug!(
+                "No manifest hash for {}, not verifying the manifest",
+                download_info.artifact_id
+            ),
+            Some(expected) if !manifest_hash_matches(&data, &expected) => {
+                error!(
+                    "Manifest hash mismatch for {}, expected {}",
+                    download_info.artifact_id, download_info.manifest_hash
+                );
+                return Err(EpicAPIError::Unknown);
+            }
+            Some(_) => {}
+        }
+        match DownloadManifest::parse(data.to_vec()) {
+            None => {
+                error!("Unable to parse the Download Manifest");
+                Err(EpicAPIError::Unknown)
+            }
+            Some(man) => Ok(man),
+        }
     }
 
     pub async fn fab_library_items(
         &mut self,
         account_id: String,
+        options: FabLibraryOptions<'_>,
     ) -> Result<FabLibrary, EpicAPIError> {
+        options.validate()?;
         let mut library = FabLibrary::default();
 
         loop {
-            let url = match &library.cursors.next {
-                None => {
-                    format!(
-                        "https://www.fab.com/e/accounts/{}/ue/library?count=100",
-                        account_id
-                    )
+            match self
+                .fab_library_page(&account_id, library.cursors.next.clone(), options)
+                .await
+            {
+                Ok(mut api_library) => {
+                    library.cursors.next = api_library.cursors.next;
+                    library.results.append(api_library.results.borrow_mut());
                 }
-                Some(c) => {
-                    format!(
-                        "https://www.fab.com/e/accounts/{}/ue/library?cursor={}&count=100",
-                        account_id, c
-                    )
+                Err(_) => {
+                    library.cursors.next = None;
                 }
-            };
+            }
+            if library.cursors.next.is_none() {
+                break;
+            }
+        }
+
+        Ok(library)
+    }
 
+    pub async fn fab_library_items_until(
+        &self,
+        account_id: String,
+        predicate: impl Fn(&FabAsset) -> bool,
+    ) -> (Result<Option<FabAsset>, EpicAPIError>, FabLibrary) {
+        let mut library = FabLibrary::default();
+        loop {
             match self
-                .authorized_get_client(Url::parse(&url).unwrap())
-                .send()
+                .fab_library_page(
+                    &account_id,
+                    library.cursors.next.clone(),
+                    FabLibraryOptions::default(),
+                )
                 .await
             {
-                Ok(response) => {
-                    if response.status() == reqwest::StatusCode::OK {
-                        let text = response.text().await.unwrap();
-                        match serde_json::from_str::<FabLibrary>(&text) {
-                            Ok(mut api_library) => {
-                                library.cursors.next = api_library.cursors.next;
-                                library.results.append(api_library.results.borrow_mut());
-                            }
-                            Err(e) => {
-                                error!("{:?}", e);
-                                debug!("{}", text);
-                                library.cursors.next = None;
-                            }
-                        }
-                    } else {
-                        debug!("{:?}", response.headers());
-                        warn!(
-                            "{} result: {}",
-                            response.status(),
-                            response.text().await.unwrap()
-                        );
+                Ok(mut page) => {
+                    library.cursors.next = page.cursors.next;
+                    let found = page.results.iter().find(|asset| predicate(asset)).cloned();
+                    library.results.append(page.results.borrow_mut());
+                    if found.is_some() {
+                        return (Ok(found), library);
                     }
                 }
                 Err(e) => {
-                    error!("{:?}", e);
-                    library.cursors.next = None;
+                    warn!("Fab library page failed: {}", e);
+                    return (Err(e), library);
                 }
             }
             if library.cursors.next.is_none() {
-                break;
+                return (Ok(None), library);
             }
         }
+    }
 
-        Ok(library)
+    pub(crate) fn fab_library_request(
+        &self,
+        account_id: &str,
+        cursor: Option<String>,
+        options: FabLibraryOptions<'_>,
+    ) -> RequestBuilder {
+        let mut url = self
+            .fab_host()
+            .join(&format!(
+                "e/accounts/{}/{}/library",
+                account_id, options.target
+            ))
+            .unwrap();
+        if let Some(cursor) = cursor {
+            url.query_pairs_mut().append_pair("cursor", &cursor);
+        }
+        url.query_pairs_mut()
+            .append_pair("count", &options.count.to_string());
+        self.authorized_get_client(url)
+            .header(ACCEPT_LANGUAGE, options.locale)
+    }
+
+    pub async fn fab_library_page(
+        &self,
+        account_id: &str,
+        cursor: Option<String>,
+        options: FabLibraryOptions<'_>,
+    ) -> Result<FabLibrary, EpicAPIError> {
+        options.validate()?;
+        self.ensure_fab_session().await;
+        self.get_json(self.fab_library_request(account_id, cursor, options))
+            .await
+    }
+
+    pub fn fab_library_stream(
+        &self,
+        account_id: String,
+    ) -> impl Stream<Item = Result<FabAsset, EpicAPIError>> + '_ {
+        // The state is the cursor of the next page to fetch, None once there are no more pages
+        stream::unfold(Some(None), move |cursor: Option<Option<String>>| {
+            let account_id = account_id.clone();
+            async move {
+                let cursor = cursor?;
+                match self
+                    .fab_library_page(&account_id, cursor, FabLibraryOptions::default())
+                    .await
+                {
+                    Ok(page) => {
+                        let next = page.cursors.next.map(Some);
+                        Some((
+                            stream::iter(page.results.into_iter().map(Ok)).left_stream(),
+                            next,
+                        ))
+                    }
+                    Err(e) => Some((stream::once(async { Err(e) }).right_stream(), None)),
+                }
+            }
+        })
+        .flatten()
+    }
+}
+
+/// Compare the data with a SHA-1 or SHA-256 hash, chosen by the hash length
+fn manifest_hash_matches(data: &[u8], expected: &[u8]) -> bool {
+    match expected.len() {
+        20 => Sha1::digest(data).as_slice() == expected,
+        _ => Sha256::digest(data).as_slice() == expected,
+    }
+}
+
+/// Map a 403 response body of the FAB manifest endpoint to an error
+///
+/// A JSON error body means the request is forbidden for good, for example when the asset is not owned.
+/// Anything else is treated as temporary throttling.
+fn fab_forbidden_error(body: &str) -> EpicAPIError {
+    match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(serde_json::Value::Object(error)) => {
+            let message = ["detail", "message", "errorMessage", "errorCode"]
+                .iter()
+                .find_map(|key| error.get(*key).and_then(|value| value.as_str()))
+                .map(|message| message.to_string())
+                .unwrap_or_else(|| body.to_string());
+            warn!("Fab manifest forbidden: {}", message);
+            EpicAPIError::APIError(message)
+        }
+        _ => EpicAPIError::FabTimeout,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::error::EpicAPIError;
+    use crate::api::fab::{fab_forbidden_error, FabLibraryOptions};
+    use crate::api::mock::{response, MockServer};
+    use crate::api::types::fab_asset_manifest::{
+        DistributionPoint, DownloadInfo, FabAssetManifest,
+    };
+    use crate::api::types::fab_library::{Cursor, FabAsset, FabLibrary};
+    use crate::api::{ClientConfig, EpicAPI};
+    use sha1::{Digest, Sha1};
+    use sha2::Sha256;
+
+    #[test]
+    fn library_request_options() {
+        let request = EpicAPI::new()
+            .fab_library_request("account", None, FabLibraryOptions::default())
+            .build()
+            .unwrap();
+        assert_eq!(request.url().path(), "/e/accounts/account/ue/library");
+        assert_eq!(request.url().query(), Some("count=100"));
+        assert_eq!(request.headers()["Accept-Language"], "en");
+        let options = FabLibraryOptions {
+            locale: "de",
+            target: "unity",
+            count: 25,
+        };
+        let request = EpicAPI::new()
+            .fab_library_request("account", Some("next".to_string()), options)
+            .build()
+            .unwrap();
+        assert_eq!(request.url().path(), "/e/accounts/account/unity/library");
+        assert_eq!(request.url().query(), Some("cursor=next&count=25"));
+        assert_eq!(request.headers()["Accept-Language"], "de");
+        let request = EpicAPI::new()
+            .fab_library_request(
+                "account",
+                Some("eyJ0+/=&x".to_string()),
+                FabLibraryOptions::default(),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().query(),
+            Some("cursor=eyJ0%2B%2F%3D%26x&count=100")
+        );
+    }
+
+    #[tokio::test]
+    async fn library_page_size_validated() {
+        let api = EpicAPI::new();
+        for count in [0, 101] {
+            let options = FabLibraryOptions {
+                count,
+                ..Default::default()
+            };
+            assert!(matches!(
+                api.fab_library_page("account", None, options).await,
+                Err(EpicAPIError::InvalidParams)
+            ));
+        }
+    }
+
+    #[test]
+    fn forbidden_body_classification() {
+        assert!(matches!(
+            fab_forbidden_error("<!DOCTYPE html><title>Just a moment...</title>"),
+            EpicAPIError::FabTimeout
+        ));
+        assert!(matches!(fab_forbidden_error(""), EpicAPIError::FabTimeout));
+        match fab_forbidden_error(r#"{"detail":"You do not own this asset"}"#) {
+            EpicAPIError::APIError(message) => assert_eq!(message, "You do not own this asset"),
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn fab_session_established_once() {
+        let server = MockServer::start(vec![
+            response(500, &[], ""),
+            response(200, &[("Set-Cookie", "fab_csrftoken=token; Path=/")], ""),
+            response(200, &[], ""),
+        ])
+        .await;
+        let api = EpicAPI::new();
+        api.ensure_fab_session_at(server.url.as_str()).await;
+        assert_eq!(server.requests().len(), 1);
+        assert!(!api.fab_session.initialized());
+
+        api.ensure_fab_session_at(server.url.as_str()).await;
+        api.ensure_fab_session_at(server.url.as_str()).await;
+        assert_eq!(server.requests().len(), 2);
+        assert!(api.fab_session.initialized());
+
+        // Calls made after the session is established send its cookies
+        api.authorized_get_client(server.url.clone())
+            .send()
+            .await
+            .unwrap();
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].contains("fab_csrftoken=token"));
+    }
+
+    #[tokio::test]
+    async fn download_manifest_falls_back_to_next_point() {
+        let body = r#"{"ManifestFileVersion":"013000000000","bIsFileData":false,"AppID":"000000000000","AppNameString":"Asset","BuildVersionString":"1.0","LaunchExeString":"","LaunchCommand":"","PrereqName":"","PrereqPath":"","PrereqArgs":"","FileManifestList":[],"ChunkHashList":{},"DataGroupList":{},"ChunkFilesizeList":{}}"#;
+        let expired = MockServer::start(vec![response(200, &[], body)]).await;
+        let failing = MockServer::start(vec![response(500, &[], "")]).await;
+        let working = MockServer::start(vec![response(200, &[], body)]).await;
+        let now = time::OffsetDateTime::now_utc();
+        let point = |server: &MockServer, expiration: time::OffsetDateTime| DistributionPoint {
+            manifest_url: server.url.join("asset.manifest").unwrap().to_string(),
+            signature_expiration: expiration,
+        };
+        let info = DownloadInfo {
+            artifact_id: "artifact".to_string(),
+            distribution_points: vec![
+                point(&expired, now - time::Duration::hours(1)),
+                point(&failing, now + time::Duration::hours(1)),
+                point(&working, now + time::Duration::hours(1)),
+            ],
+            ..Default::default()
+        };
+        let (downloaded, used) = EpicAPI::new()
+            .fab_download_manifest_with_fallback(info)
+            .await
+            .unwrap();
+        assert_eq!(downloaded.app_name_string, "Asset");
+        assert_eq!(
+            used.manifest_url,
+            working.url.join("asset.manifest").unwrap().as_str()
+        );
+        assert!(expired.requests().is_empty());
+        assert_eq!(failing.requests().len(), 1);
+        assert_eq!(working.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn asset_manifest_all_platforms_skips_failures() {
+        let manifest = serde_json::to_string(&FabAssetManifest {
+            download_info: vec![DownloadInfo {
+                artifact_id: "artifact".to_string(),
+                ..Default::default()
+            }],
+        })
+        .unwrap();
+        let server = MockServer::start(vec![
+            response(200, &[], ""),
+            response(200, &[], &manifest),
+            response(500, &[], ""),
+        ])
+        .await;
+        let api = EpicAPI::with_config(ClientConfig {
+            fab_url: Some(server.url.clone()),
+            retry_attempts: Some(1),
+            ..Default::default()
+        });
+        let manifests = api
+            .fab_asset_manifest_all_platforms("artifact", "ns", "asset", &["Windows", "Mac"])
+            .await;
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].starts_with("GET / "));
+        let platform = |request: &str| {
+            assert!(request.starts_with("POST /e/artifacts/artifact/manifest "));
+            let body = &request[request.find("\r\n\r\n").unwrap() + 4..];
+            serde_json::from_str::<serde_json::Value>(body).unwrap()["platform"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        let (succeeded, failed) = (platform(&requests[1]), platform(&requests[2]));
+        assert_ne!(succeeded, failed);
+        assert_eq!(manifests.len(), 1);
+        assert_eq!(manifests[&succeeded][0].artifact_id, "artifact");
+        assert!(!manifests.contains_key(&failed));
+    }
+
+    #[tokio::test]
+    async fn library_items_until_keeps_partial_results_on_error() {
+        let page = serde_json::to_string(&FabLibrary {
+            cursors: Cursor {
+                next: Some("second".to_string()),
+            },
+            results: vec![FabAsset {
+                asset_id: "first".to_string(),
+                ..Default::default()
+            }],
+        })
+        .unwrap();
+        let server = MockServer::start(vec![
+            response(200, &[], ""),
+            response(200, &[], &page),
+            response(500, &[], ""),
+        ])
+        .await;
+        let api = EpicAPI::with_config(ClientConfig {
+            fab_url: Some(server.url.clone()),
+            retry_attempts: Some(1),
+            ..Default::default()
+        });
+        let (found, library) = api
+            .fab_library_items_until("account".to_string(), |asset| asset.asset_id == "missing")
+            .await;
+        assert!(found.is_err());
+        assert_eq!(library.results.len(), 1);
+        assert_eq!(library.results[0].asset_id, "first");
+        // The cursor still points at the failed page, so the caller can resume from there
+        assert_eq!(library.cursors.next, Some("second".to_string()));
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn download_manifest_hash_checked() {
+        let body = r#"{"ManifestFileVersion":"013000000000","bIsFileData":false,"AppID":"000000000000","AppNameString":"Asset","BuildVersionString":"1.0","LaunchExeString":"","LaunchCommand":"","PrereqName":"","PrereqPath":"","PrereqArgs":"","FileManifestList":[],"ChunkHashList":{},"DataGroupList":{},"ChunkFilesizeList":{}}"#;
+        for (manifest_hash, valid) in [
+            (format!("{:x}", Sha1::digest(body)), true),
+            (format!("{:X}", Sha256::digest(body)), true),
+            (format!("{:x}", Sha1::digest(b"other")), false),
+            (format!("{:x}", Sha256::digest(b"other")), false),
+        ] {
+            let server = MockServer::start(vec![response(200, &[], body)]).await;
+            let info = DownloadInfo {
+                artifact_id: "artifact".to_string(),
+                manifest_hash,
+                distribution_points: vec![DistributionPoint {
+                    manifest_url: server.url.join("asset.manifest").unwrap().to_string(),
+                    signature_expiration: time::OffsetDateTime::now_utc()
+                        + time::Duration::hours(1),
+                }],
+                ..Default::default()
+            };
+            let result = EpicAPI::new().fab_download_manifest_auto(info).await;
+            assert_eq!(result.is_ok(), valid);
+        }
     }
 }
@@ -14,14 +14,7 @@ impl EpicAPI {
     ) -> Result<bool, EpicAPIError> {
         let params = match exchange_token {
             None => match authorization_code {
-                None => [
-                    ("grant_type".to_string(), "refresh_token".to_string()),
-                    (
-                        "refresh_token".to_string(),
-                        self.user_data.refresh_token.clone().unwrap(),
-                    ),
-                    ("token_type".to_string(), "eg1".to_string()),
-                ],
+                None => return self.refresh_token().await.map(|_| true),
                 Some(auth) => [
                     ("grant_type".to_string(), "authorization_code".to_string()),
                     ("code".to_string(), auth),
@@ -35,64 +28,76 @@ impl EpicAPI {
             ],
         };
 
-        match self
+        self.token_request(&params).await
+    }
+
+    pub async fn refresh_token(&self) -> Result<(), EpicAPIError> {
+        let refresh_token = match self.user_data().refresh_token {
+            None => {
+                error!("No refresh token available");
+                return Err(EpicAPIError::InvalidCredentials);
+            }
+            Some(token) => token,
+        };
+        let params = [
+            ("grant_type".to_string(), "refresh_token".to_string()),
+            ("refresh_token".to_string(), refresh_token),
+            ("token_type".to_string(), "eg1".to_string()),
+        ];
+        self.token_request(&params).await.map(|_| ())
+    }
+
+    async fn token_request(&self, params: &[(String, String)]) -> Result<bool, EpicAPIError> {
+        let url = match &self.config.token_url {
+            None => Url::parse(
+                "https://account-public-service-prod03.ol.epicgames.com/account/api/oauth/token",
+            )
+            .unwrap(),
+            Some(url) => url.clone(),
+        };
+        let response = self
             .client
-            .post("https://account-public-service-prod03.ol.epicgames.com/account/api/oauth/token")
-            .form(&params)
+            .post(url)
+            .form(params)
             .basic_auth(
                 "34a02cf8f4414e29b15921876da36f9a",
                 Some("daafbccc737745039dffe53d94fc76cf"),
             )
             .send()
-            .await
-        {
-            Ok(response) => self.handle_login_response(response).await,
-            Err(e) => {
-                error!("{:?}", e);
-                Err(EpicAPIError::Unknown)
-            }
-        }
+            .await?;
+        self.handle_login_response(response).await
     }
 
-    async fn handle_login_response(&mut self, response: Response) -> Result<bool, EpicAPIError> {
+    async fn handle_login_response(&self, response: Response) -> Result<bool, EpicAPIError> {
         if response.status() == reqwest::StatusCode::INTERNAL_SERVER_ERROR {
             error!("Server Error");
             return Err(EpicAPIError::Server);
         }
-        let new: UserData = match response.json().await {
-            Ok(data) => data,
-            Err(e) => {
-                error!("{:?}", e);
-                return Err(EpicAPIError::Unknown);
-            }
-        };
+        let new: UserData = serde_json::from_slice(&response.bytes().await?)?;
 
-        self.user_data.update(new);
+        self.update_user_data(new);
 
-        if let Some(m) = &self.user_data.error_message {
+        if let Some(m) = self.user_data().error_message {
             error!("{}", m);
-            return Err(EpicAPIError::APIError(m.to_string()));
+            return Err(EpicAPIError::APIError(m));
         }
         Ok(true)
     }
 
     pub async fn resume_session(&mut self) -> Result<bool, EpicAPIError> {
-        match self.authorized_get_client(Url::parse("https://account-public-service-prod03.ol.epicgames.com/account/api/oauth/verify").unwrap()).send().await {
-            Ok(response) => {
-                self.handle_login_response(response).await
-            }
-            Err(e) => {
-                error!("{:?}", e);
-                Err(EpicAPIError::Unknown)
-            }
-        }
+        let response = self.send_with_retry(self.authorized_get_client(Url::parse("https://account-public-service-prod03.ol.epicgames.com/account/api/oauth/verify").unwrap())).await?;
+        self.handle_login_response(response).await
     }
 
     pub async fn invalidate_sesion(&mut self) -> bool {
-        if let Some(access_token) = &self.user_data.access_token {
+        if let Some(access_token) = self.user_data().access_token {
             let url = format!("https://account-public-service-prod03.ol.epicgames.com/account/api/oauth/sessions/kill/{}", access_token);
-            let client = EpicAPI::build_client().build().unwrap();
-            match client.delete(Url::from_str(&url).unwrap()).send().await {
+            match self
+                .client
+                .delete(Url::from_str(&url).unwrap())
+                .send()
+                .await
+            {
                 Ok(_) => {
                     info!("Session invalidated");
                     return true;
@@ -104,4 +109,27 @@ impl EpicAPI {
         };
         false
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::api::error::EpicAPIError;
+    use crate::api::EpicAPI;
+
+    #[tokio::test]
+    async fn refresh_without_refresh_token() {
+        let api = EpicAPI::new();
+        assert!(matches!(
+            api.refresh_token().await,
+            Err(EpicAPIError::InvalidCredentials)
+        ));
+    }
+
+    #[tokio::test]
+    async fn start_session_without_refresh_token() {
+        let mut api = EpicAPI::new();
+        assert!(matches!(
+            api.start_session(None, None).await,
+            Err(EpicAPIError::InvalidCredentials)
+        ));
+    }
+}
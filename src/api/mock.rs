@@ -0,0 +1,110 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+
+/// Response that is never sent, for testing timeouts
+pub(crate) const STALLED: &str = "stalled";
+
+/// Minimal HTTP server answering requests with canned responses, in order
+///
+/// Every connection serves a single request and is closed afterwards.
+/// Once the responses run out the server answers with 404.
+/// A `STALLED` response keeps the connection open without answering.
+pub(crate) struct MockServer {
+    pub(crate) url: Url,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub(crate) async fn start<T: Into<Vec<u8>>>(responses: Vec<T>) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let responses: Vec<Vec<u8>> = responses.into_iter().map(Into::into).collect();
+        tokio::spawn(async move {
+            let mut responses = responses.into_iter();
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let request = read_request(&mut socket).await;
+                recorded.lock().unwrap().push(request);
+                let response = responses
+                    .next()
+                    .unwrap_or_else(|| response(404, &[], "").into_bytes());
+                if response == STALLED.as_bytes() {
+                    tokio::spawn(async move {
+                        tokio::time::sleep(Duration::from_secs(30)).await;
+                        drop(socket);
+                    });
+                    continue;
+                }
+                let _ = socket.write_all(&response).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        MockServer { url, requests }
+    }
+
+    /// Get the received requests, head and body
+    pub(crate) fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Build a raw HTTP response with a binary body
+pub(crate) fn binary_response(status: u16, body: &[u8]) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+/// Build a raw HTTP response
+pub(crate) fn response(status: u16, headers: &[(&str, &str)], body: &str) -> String {
+    let mut response = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(body);
+    response
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+    let mut data = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        let read = socket.read(&mut buffer).await.unwrap_or(0);
+        if read == 0 {
+            break;
+        }
+        data.extend_from_slice(&buffer[..read]);
+        let text = String::from_utf8_lossy(&data);
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text[..end]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    if name.eq_ignore_ascii_case("content-length") {
+                        value.trim().parse::<usize>().ok()
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or(0);
+            if data.len() >= end + 4 + length {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&data).to_string()
+}
@@ -1,6 +1,18 @@
-use reqwest::header::HeaderMap;
-use reqwest::{Client, ClientBuilder, RequestBuilder};
+use crate::api::error::EpicAPIError;
+use fab::FabLibraryOptions;
+use log::{debug, error, warn};
+use reqwest::header::{
+    HeaderMap, HeaderValue, AUTHORIZATION, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{Client, ClientBuilder, RequestBuilder, Response, StatusCode};
+use reqwest_cookie_store::CookieStoreMutex;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::OnceCell;
 use types::account::UserData;
+use types::request::{RequestDescription, RequestOperation};
 use url::Url;
 
 /// Module holding the API types
@@ -20,25 +32,93 @@ pub mod account;
 
 /// EGS Methods
 pub mod egs;
+
+/// Download Methods
+pub mod download;
+
+/// Mock HTTP server for tests
+#[cfg(test)]
+pub(crate) mod mock;
+
 /// Session Handling
 pub mod login;
 
+/// Settings applied to every HTTP client the API builds
 #[derive(Default, Debug, Clone)]
+pub(crate) struct ClientConfig {
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+    pub(crate) pool_idle_timeout: Option<Duration>,
+    pub(crate) prefer_http2: Option<bool>,
+    pub(crate) retry_attempts: Option<u32>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) download_timeout: Option<Duration>,
+    /// Replaces the OAuth token endpoint, only set by tests
+    pub(crate) token_url: Option<Url>,
+    /// Replaces the catalog service host, only set by tests
+    pub(crate) catalog_url: Option<Url>,
+    /// Replaces the launcher service host, only set by tests
+    pub(crate) launcher_url: Option<Url>,
+    /// Replaces the FAB host, only set by tests
+    pub(crate) fab_url: Option<Url>,
+}
+
+/// Number of attempts for requests failing with a network error, unless configured
+pub(crate) const DEFAULT_RETRY_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry, doubled with every further attempt
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Validators of a previous response, sent back to make the request conditional
+#[derive(Default, Debug, Clone)]
+struct Validators {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+}
+
+#[derive(Debug, Clone)]
 pub(crate) struct EpicAPI {
+    /// Used for every request, so all of them share the connection pool and the session cookies
     client: Client,
-    pub(crate) user_data: UserData,
+    config: ClientConfig,
+    cookies: Arc<CookieStoreMutex>,
+    fab_session: Arc<OnceCell<()>>,
+    /// Shared with clones like the cookies, so a refresh during any request updates them all
+    user_data: Arc<RwLock<UserData>>,
+    /// Held while refreshing, so concurrent rejected requests refresh the session only once
+    refresh_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Validators of the last successful conditional request, keyed by url
+    validators: Arc<Mutex<HashMap<String, Validators>>>,
+}
+
+impl Default for EpicAPI {
+    fn default() -> Self {
+        EpicAPI::new()
+    }
 }
 
 impl EpicAPI {
     pub fn new() -> Self {
-        let client = EpicAPI::build_client().build().unwrap();
+        EpicAPI::with_config(ClientConfig::default())
+    }
+
+    pub fn with_config(config: ClientConfig) -> Self {
+        let cookies: Arc<CookieStoreMutex> = Default::default();
+        let client = EpicAPI::client_builder(&config)
+            .cookie_provider(cookies.clone())
+            .build()
+            .unwrap();
         EpicAPI {
             client,
+            config,
+            cookies,
+            fab_session: Default::default(),
             user_data: Default::default(),
+            refresh_lock: Default::default(),
+            validators: Default::default(),
         }
     }
 
-    fn build_client() -> ClientBuilder {
+    fn default_headers() -> HeaderMap {
         let mut headers = HeaderMap::new();
         headers.insert(
             "User-Agent",
@@ -50,37 +130,566 @@ impl EpicAPI {
             "X-Epic-Correlation-ID",
             "UE4-c176f7154c2cda1061cc43ab52598e2b-93AFB486488A22FDF70486BD1D883628-BFCD88F649E997BA203FF69F07CE578C".parse().unwrap()
         );
-        reqwest::Client::builder()
-            .default_headers(headers)
+        headers
+    }
+
+    /// Compressed responses are accepted and decoded transparently.
+    /// Chunks are already zlib compressed and the CDN serves them as is.
+    fn client_builder(config: &ClientConfig) -> ClientBuilder {
+        let mut builder = reqwest::Client::builder()
+            .default_headers(EpicAPI::default_headers())
             .cookie_store(true)
+            .gzip(true)
+            .brotli(true)
+            .deflate(true);
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(max) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = config.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        match config.prefer_http2 {
+            None => {}
+            Some(true) => builder = builder.http2_adaptive_window(true),
+            Some(false) => builder = builder.http1_only(),
+        }
+        #[cfg(feature = "native-tls")]
+        let builder = builder.use_native_tls();
+        #[cfg(all(feature = "rustls-tls", not(feature = "native-tls")))]
+        let builder = builder.use_rustls_tls();
+        builder
+    }
+
+    /// Get a copy of the session data
+    pub(crate) fn user_data(&self) -> UserData {
+        self.user_data.read().unwrap().clone()
+    }
+
+    /// Update the present values of the session data
+    pub(crate) fn update_user_data(&self, new: UserData) {
+        self.user_data.write().unwrap().update(new);
+    }
+
+    /// Apply the download timeout, which replaces the client timeout for this request
+    ///
+    /// The timeout of the call takes precedence over the configured download timeout.
+    fn with_download_timeout(
+        &self,
+        rb: RequestBuilder,
+        timeout: Option<Duration>,
+    ) -> RequestBuilder {
+        match timeout.or(self.config.download_timeout) {
+            None => rb,
+            Some(timeout) => rb.timeout(timeout),
+        }
     }
 
     fn authorized_get_client(&self, url: Url) -> RequestBuilder {
-        let client = EpicAPI::build_client().build().unwrap();
-        self.set_authorization_header(client.get(url))
+        debug!("GET {}", url);
+        self.set_authorization_header(self.client.get(url))
     }
 
     fn authorized_post_client(&self, url: Url) -> RequestBuilder {
-        let client = EpicAPI::build_client().build().unwrap();
-        self.set_authorization_header(client.post(url))
+        debug!("POST {}", url);
+        self.set_authorization_header(self.client.post(url))
+    }
+
+    /// Send an authorized request, refreshing the session once when it is rejected
+    ///
+    /// Epic can revoke a token before its expiry, so on a 401 the session is refreshed with the
+    /// refresh token and the request is sent again with the new token, exactly once.
+    /// Refreshes are serialized, a request rejected with a token that was already replaced
+    /// is retried with the current token without refreshing again.
+    async fn send_with_retry(&self, builder: RequestBuilder) -> Result<Response, reqwest::Error> {
+        let retry = builder.try_clone();
+        let response = self.send_with_network_retry(builder).await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        let retry = match retry {
+            None => return Ok(response),
+            Some(retry) => retry,
+        };
+        if self.user_data().refresh_token.is_none() {
+            return Ok(response);
+        }
+        let (client, request) = retry.build_split();
+        let mut request = request?;
+        let rejected = request.headers_mut().remove(AUTHORIZATION);
+        {
+            let _guard = self.refresh_lock.lock().await;
+            if rejected.as_ref().map(|value| value.as_bytes())
+                == Some(self.authorization().as_bytes())
+            {
+                warn!("Request unauthorized, refreshing the session");
+                if let Err(e) = self.refresh_token().await {
+                    warn!("Unable to refresh the session: {}", e);
+                    return Ok(response);
+                }
+            } else {
+                debug!("Request unauthorized, the session was already refreshed");
+            }
+        }
+        let retry = self.set_authorization_header(RequestBuilder::from_parts(client, request));
+        self.send_with_network_retry(retry).await
+    }
+
+    /// Send the request, retrying connection level errors with an increasing delay
+    ///
+    /// Requests that got a response are never retried, whatever the status.
+    async fn send_with_network_retry(
+        &self,
+        builder: RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        let attempts = self
+            .config
+            .retry_attempts
+            .unwrap_or(DEFAULT_RETRY_ATTEMPTS)
+            .max(1);
+        let mut delay = RETRY_DELAY;
+        for attempt in 1..attempts {
+            let request = match builder.try_clone() {
+                None => break,
+                Some(request) => request,
+            };
+            match request.send().await {
+                Err(e) if e.is_connect() || e.is_timeout() || e.is_request() => {
+                    warn!(
+                        "Request failed on attempt {}/{}, retrying in {:?}: {}",
+                        attempt, attempts, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+        builder.send().await
+    }
+
+    /// Send an authorized request and decode the JSON body of the response, see `json_response`
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        builder: RequestBuilder,
+    ) -> Result<T, EpicAPIError> {
+        let response = self.send_with_retry(builder).await.map_err(|e| {
+            error!("{:?}", e);
+            e
+        })?;
+        EpicAPI::json_response(response).await
+    }
+
+    /// Like `get_json`, but conditional on the response having changed since the last call
+    ///
+    /// The `ETag` and `Last-Modified` of the previous successful response for the same url are
+    /// sent as `If-None-Match` and `If-Modified-Since`, `None` is returned when the server
+    /// answers 304 Not Modified.
+    async fn get_json_if_modified<T: DeserializeOwned>(
+        &self,
+        builder: RequestBuilder,
+    ) -> Result<Option<T>, EpicAPIError> {
+        let (client, request) = builder.build_split();
+        let mut request = request?;
+        let key = request.url().to_string();
+        let previous = self.validators.lock().unwrap().get(&key).cloned();
+        if let Some(previous) = previous {
+            if let Some(etag) = previous.etag {
+                request.headers_mut().insert(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = previous.last_modified {
+                request
+                    .headers_mut()
+                    .insert(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = self
+            .send_with_retry(RequestBuilder::from_parts(client, request))
+            .await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            debug!("{} not modified", key);
+            return Ok(None);
+        }
+        let validators = Validators {
+            etag: response.headers().get(ETAG).cloned(),
+            last_modified: response.headers().get(LAST_MODIFIED).cloned(),
+        };
+        let result = EpicAPI::json_response(response).await?;
+        self.validators.lock().unwrap().insert(key, validators);
+        Ok(Some(result))
+    }
+
+    /// Decode the JSON body of a successful response
+    ///
+    /// Any other status is logged with the body and fails with `EpicAPIError::Unknown`.
+    async fn json_response<T: DeserializeOwned>(response: Response) -> Result<T, EpicAPIError> {
+        if response.status() != StatusCode::OK {
+            debug!("{:?}", response.headers());
+            warn!(
+                "{} result: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+            return Err(EpicAPIError::Unknown);
+        }
+        let body = response.bytes().await?;
+        serde_json::from_slice(&body).map_err(|e| {
+            error!("{:?}", e);
+            debug!("{}", String::from_utf8_lossy(&body));
+            e.into()
+        })
+    }
+
+    pub fn export_cookies(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        let store = self.cookies.lock().unwrap();
+        if let Err(e) =
+            cookie_store::serde::json::save_incl_expired_and_nonpersistent(&store, &mut data)
+        {
+            error!("Unable to export cookies: {:?}", e);
+        }
+        data
+    }
+
+    pub fn import_cookies(&mut self, data: &[u8]) -> Result<(), EpicAPIError> {
+        match cookie_store::serde::json::load(data) {
+            Ok(store) => {
+                *self.cookies.lock().unwrap() = store;
+                Ok(())
+            }
+            Err(e) => {
+                error!("Unable to import cookies: {:?}", e);
+                Err(EpicAPIError::InvalidParams)
+            }
+        }
+    }
+
+    /// Value of the Authorization header for the current session
+    fn authorization(&self) -> String {
+        let user_data = self.user_data.read().unwrap();
+        format!(
+            "{} {}",
+            user_data
+                .token_type
+                .as_ref()
+                .unwrap_or(&"bearer".to_string()),
+            user_data.access_token.as_ref().unwrap_or(&"".to_string())
+        )
     }
 
     fn set_authorization_header(&self, rb: RequestBuilder) -> RequestBuilder {
-        rb.header(
-            "Authorization",
-            format!(
-                "{} {}",
-                self.user_data
-                    .token_type
-                    .as_ref()
-                    .unwrap_or(&"bearer".to_string()),
-                self.user_data
-                    .access_token
-                    .as_ref()
-                    .unwrap_or(&"".to_string())
+        rb.header("Authorization", self.authorization())
+    }
+
+    pub fn describe_request(&self, operation: RequestOperation) -> Option<RequestDescription> {
+        let rb = match operation {
+            RequestOperation::Assets { platform, label } => self.assets_request(platform, label),
+            RequestOperation::LibraryItems {
+                include_metadata,
+                cursor,
+            } => self.library_items_request(include_metadata, cursor),
+            RequestOperation::FabAssetManifest {
+                artifact_id,
+                namespace,
+                asset_id,
+                platform,
+            } => self.fab_asset_manifest_request(
+                &artifact_id,
+                &namespace,
+                &asset_id,
+                platform.as_deref(),
             ),
-        )
+            RequestOperation::FabLibrary { account_id, cursor } => {
+                self.fab_library_request(&account_id, cursor, FabLibraryOptions::default())
+            }
+        };
+        let request = rb.build().ok()?;
+        // Client default headers are only merged in when sending, so add them here
+        let mut headers = EpicAPI::default_headers();
+        for (name, value) in request.headers() {
+            headers.insert(name, value.clone());
+        }
+        Some(RequestDescription {
+            method: request.method().to_string(),
+            url: request.url().clone(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).to_string(),
+                    )
+                })
+                .collect(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|bytes| String::from_utf8_lossy(bytes).to_string()),
+        })
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::error::EpicAPIError;
+    use crate::api::mock::{response, MockServer};
+    use crate::api::types::account::UserData;
+    use crate::api::types::request::RequestOperation;
+    use crate::api::{ClientConfig, EpicAPI};
+    use url::Url;
+
+    #[test]
+    fn cookies_round_trip() {
+        let api = EpicAPI::new();
+        let url = Url::parse("https://www.fab.com/").unwrap();
+        api.cookies
+            .lock()
+            .unwrap()
+            .parse("fab_csrftoken=token; Path=/", &url)
+            .unwrap();
+        let data = api.export_cookies();
 
-    
+        let mut restored = EpicAPI::new();
+        assert!(restored.import_cookies(&data).is_ok());
+        let store = restored.cookies.lock().unwrap();
+        let cookie = store.get("www.fab.com", "/", "fab_csrftoken").unwrap();
+        assert_eq!(cookie.value(), "token");
+        drop(store);
+        assert!(restored.import_cookies(b"not json").is_err());
+    }
+
+    #[test]
+    fn describe_operations() {
+        let api = EpicAPI::new();
+        api.update_user_data(UserData {
+            access_token: Some("token".to_string()),
+            ..Default::default()
+        });
+        let cases = vec![
+            (
+                RequestOperation::Assets {
+                    platform: None,
+                    label: None,
+                },
+                "GET",
+                "https://launcher-public-service-prod06.ol.epicgames.com/launcher/api/public/assets/Windows?label=Live",
+            ),
+            (
+                RequestOperation::LibraryItems {
+                    include_metadata: true,
+                    cursor: Some("next".to_string()),
+                },
+                "GET",
+                "https://library-service.live.use1a.on.epicgames.com/library/api/public/items?includeMetadata=true&cursor=next",
+            ),
+            (
+                RequestOperation::FabAssetManifest {
+                    artifact_id: "artifact".to_string(),
+                    namespace: "ns".to_string(),
+                    asset_id: "asset".to_string(),
+                    platform: None,
+                },
+                "POST",
+                "https://www.fab.com/e/artifacts/artifact/manifest",
+            ),
+            (
+                RequestOperation::FabLibrary {
+                    account_id: "account".to_string(),
+                    cursor: None,
+                },
+                "GET",
+                "https://www.fab.com/e/accounts/account/ue/library?count=100",
+            ),
+        ];
+        for (operation, method, url) in cases {
+            let description = api.describe_request(operation).unwrap();
+            assert_eq!(description.method, method);
+            assert_eq!(description.url.as_str(), url);
+            assert_eq!(description.headers["authorization"], "bearer token");
+            assert!(description.headers.contains_key("user-agent"));
+        }
+        let description = api
+            .describe_request(RequestOperation::FabAssetManifest {
+                artifact_id: "artifact".to_string(),
+                namespace: "ns".to_string(),
+                asset_id: "asset".to_string(),
+                platform: Some("Mac".to_string()),
+            })
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_str(&description.body.unwrap()).unwrap();
+        assert_eq!(body["item_id"], "asset");
+        assert_eq!(body["namespace"], "ns");
+        assert_eq!(body["platform"], "Mac");
+    }
+
+    #[tokio::test]
+    async fn retry_dropped_connection() {
+        let server = MockServer::start(vec!["".to_string(), response(200, &[], "{}")]).await;
+        let api = EpicAPI::new();
+        let result = api
+            .send_with_retry(api.authorized_get_client(server.url.clone()))
+            .await
+            .unwrap();
+        assert_eq!(result.status(), reqwest::StatusCode::OK);
+        assert_eq!(server.requests().len(), 2);
+
+        let server = MockServer::start(vec![response(500, &[], "")]).await;
+        let result = api
+            .send_with_retry(api.authorized_get_client(server.url.clone()))
+            .await
+            .unwrap();
+        assert_eq!(result.status(), reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn refresh_on_unauthorized() {
+        let token = MockServer::start(vec![response(
+            200,
+            &[("Content-Type", "application/json")],
+            r#"{"access_token":"new-token","refresh_token":"new-refresh"}"#,
+        )])
+        .await;
+        let server =
+            MockServer::start(vec![response(401, &[], ""), response(200, &[], "{}")]).await;
+        let api = EpicAPI::with_config(ClientConfig {
+            token_url: Some(token.url.clone()),
+            ..Default::default()
+        });
+        api.update_user_data(UserData {
+            access_token: Some("old-token".to_string()),
+            refresh_token: Some("old-refresh".to_string()),
+            ..Default::default()
+        });
+        let result = api
+            .send_with_retry(api.authorized_get_client(server.url.clone()))
+            .await
+            .unwrap();
+        assert_eq!(result.status(), reqwest::StatusCode::OK);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("bearer old-token"));
+        assert!(requests[1].contains("bearer new-token"));
+        assert!(!requests[1].contains("old-token"));
+        assert!(token.requests()[0].contains("refresh_token=old-refresh"));
+        assert_eq!(
+            api.user_data().refresh_token,
+            Some("new-refresh".to_string())
+        );
+
+        let server =
+            MockServer::start(vec![response(401, &[], ""), response(200, &[], "{}")]).await;
+        let result = EpicAPI::new()
+            .send_with_retry(EpicAPI::new().authorized_get_client(server.url.clone()))
+            .await
+            .unwrap();
+        assert_eq!(result.status(), reqwest::StatusCode::UNAUTHORIZED);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn concurrent_unauthorized_refresh_once() {
+        let token = MockServer::start(vec![response(
+            200,
+            &[("Content-Type", "application/json")],
+            r#"{"access_token":"new-token","refresh_token":"new-refresh"}"#,
+        )])
+        .await;
+        let first = MockServer::start(vec![response(401, &[], ""), response(200, &[], "{}")]).await;
+        let second =
+            MockServer::start(vec![response(401, &[], ""), response(200, &[], "{}")]).await;
+        let api = EpicAPI::with_config(ClientConfig {
+            token_url: Some(token.url.clone()),
+            ..Default::default()
+        });
+        api.update_user_data(UserData {
+            access_token: Some("old-token".to_string()),
+            refresh_token: Some("old-refresh".to_string()),
+            ..Default::default()
+        });
+        let clone = api.clone();
+        let (a, b) = tokio::join!(
+            api.send_with_retry(api.authorized_get_client(first.url.clone())),
+            clone.send_with_retry(clone.authorized_get_client(second.url.clone()))
+        );
+        assert_eq!(a.unwrap().status(), reqwest::StatusCode::OK);
+        assert_eq!(b.unwrap().status(), reqwest::StatusCode::OK);
+        assert_eq!(token.requests().len(), 1);
+        for server in [&first, &second] {
+            let requests = server.requests();
+            assert!(requests[0].contains("bearer old-token"));
+            assert!(requests[1].contains("bearer new-token"));
+        }
+    }
+
+    #[tokio::test]
+    async fn json_errors_keep_source() {
+        let server = MockServer::start(vec![
+            response(200, &[], "{\"not\":"),
+            response(404, &[], "missing"),
+        ])
+        .await;
+        let api = EpicAPI::new();
+        let result: Result<UserData, EpicAPIError> = api
+            .get_json(api.authorized_get_client(server.url.clone()))
+            .await;
+        assert!(matches!(result, Err(EpicAPIError::Decode(_))));
+        let result: Result<UserData, EpicAPIError> = api
+            .get_json(api.authorized_get_client(server.url.clone()))
+            .await;
+        assert!(matches!(result, Err(EpicAPIError::Unknown)));
+    }
+
+    #[tokio::test]
+    async fn conditional_request() {
+        let server = MockServer::start(vec![
+            response(
+                200,
+                &[
+                    ("ETag", "\"v1\""),
+                    ("Last-Modified", "Wed, 01 Jan 2025 00:00:00 GMT"),
+                ],
+                "{}",
+            ),
+            response(304, &[], ""),
+        ])
+        .await;
+        let api = EpicAPI::new();
+        let first: Option<UserData> = api
+            .get_json_if_modified(api.authorized_get_client(server.url.clone()))
+            .await
+            .unwrap();
+        assert!(first.is_some());
+        let second: Option<UserData> = api
+            .get_json_if_modified(api.authorized_get_client(server.url.clone()))
+            .await
+            .unwrap();
+        assert!(second.is_none());
+        let requests = server.requests();
+        assert!(!requests[0].to_lowercase().contains("if-none-match"));
+        assert!(requests[1].to_lowercase().contains("if-none-match: \"v1\""));
+        assert!(requests[1]
+            .to_lowercase()
+            .contains("if-modified-since: wed, 01 jan 2025 00:00:00 gmt"));
+    }
+
+    #[tokio::test]
+    async fn downloads_share_the_session_client() {
+        let server = MockServer::start(vec![
+            response(200, &[("Set-Cookie", "session=abc; Path=/")], ""),
+            response(404, &[], ""),
+        ])
+        .await;
+        let api = EpicAPI::new();
+        api.authorized_get_client(server.url.clone())
+            .send()
+            .await
+            .unwrap();
+        assert!(api.download_chunk(&server.url, None).await.is_err());
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains("session=abc"));
+    }
 }
@@ -29,6 +29,27 @@ pub struct AccountData {
     pub tfa_enabled: bool,
 }
 
+impl AccountData {
+    /// Check whether the account is expected or known to belong to a minor
+    pub fn is_minor(&self) -> bool {
+        self.minor_expected || self.minor_status.starts_with("MINOR")
+    }
+
+    /// Check whether the account is verified
+    ///
+    /// The email has to be verified, for minors the minor status has to be verified too.
+    pub fn is_verified(&self) -> bool {
+        self.email_verified && (!self.is_minor() || self.minor_verified)
+    }
+
+    /// Check whether the account has to set a display name
+    ///
+    /// That is the case for headless accounts and accounts without a display name.
+    pub fn needs_display_name_change(&self) -> bool {
+        self.headless || self.display_name.trim().is_empty()
+    }
+}
+
 /// Structure that holds all user data
 ///
 /// Needed for login
@@ -190,3 +211,56 @@ pub struct AuthId {
     #[serde(rename = "type")]
     pub type_field: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::account::AccountData;
+
+    fn account() -> AccountData {
+        AccountData {
+            display_name: "Name".to_string(),
+            email_verified: true,
+            minor_status: "NOT_MINOR".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn adult_account() {
+        let account = account();
+        assert!(!account.is_minor());
+        assert!(account.is_verified());
+        assert!(!account.needs_display_name_change());
+    }
+
+    #[test]
+    fn unverified_email() {
+        let mut account = account();
+        account.email_verified = false;
+        assert!(!account.is_verified());
+    }
+
+    #[test]
+    fn minor_account() {
+        let mut account = account();
+        account.minor_status = "MINOR".to_string();
+        assert!(account.is_minor());
+        assert!(!account.is_verified());
+        account.minor_verified = true;
+        assert!(account.is_verified());
+
+        let mut expected = self::account();
+        expected.minor_expected = true;
+        assert!(expected.is_minor());
+    }
+
+    #[test]
+    fn display_name_change() {
+        let mut account = account();
+        account.display_name = " ".to_string();
+        assert!(account.needs_display_name_change());
+        let mut headless = self::account();
+        headless.headless = true;
+        assert!(headless.needs_display_name_change());
+    }
+}
@@ -1,7 +1,12 @@
-use chrono::{DateTime, Utc};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, TimeZone, Utc};
+use log::warn;
 use reqwest::Url;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
 
 #[allow(missing_docs)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -10,6 +15,7 @@ pub struct AssetInfo {
     pub id: String,
     pub title: Option<String>,
     pub description: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_key_images")]
     pub key_images: Option<Vec<KeyImage>>,
     pub categories: Option<Vec<Category>>,
     pub namespace: String,
@@ -121,6 +127,211 @@ impl AssetInfo {
         }
         None
     }
+
+    /// Check whether the asset is a DLC of another item
+    pub fn is_dlc(&self) -> bool {
+        self.main_game_item.is_some()
+    }
+
+    /// Get all DLC of the asset, including DLC nested in other DLC
+    pub fn all_dlc(&self) -> Vec<&AssetInfo> {
+        let mut result = Vec::new();
+        for dlc in &self.dlc_item_list {
+            result.push(dlc);
+            result.append(&mut dlc.all_dlc());
+        }
+        result
+    }
+
+    /// Get the raw value of a custom attribute
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.custom_attributes
+            .as_ref()?
+            .get(key)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /// Get a custom attribute as a boolean
+    pub fn attr_bool(&self, key: &str) -> Option<bool> {
+        self.custom_attributes.as_ref()?.get(key)?.as_bool()
+    }
+
+    /// Get a custom attribute as a number
+    pub fn attr_i64(&self, key: &str) -> Option<i64> {
+        self.custom_attributes.as_ref()?.get(key)?.as_i64()
+    }
+
+    /// Get the install size in bytes from the `InstallSize` custom attribute
+    pub fn install_size(&self) -> Option<u64> {
+        self.attr_i64("InstallSize")
+            .and_then(|size| u64::try_from(size).ok())
+    }
+
+    /// Check whether the asset can run offline, from the `CanRunOffline` custom attribute
+    pub fn can_run_offline(&self) -> Option<bool> {
+        self.attr_bool("CanRunOffline")
+    }
+
+    /// Get the kind of the asset from its categories
+    pub fn asset_kind(&self) -> AssetKind {
+        let paths: Vec<String> = self
+            .categories
+            .iter()
+            .flatten()
+            .map(|category| category.path.to_lowercase())
+            .collect();
+        let any = |f: fn(&str) -> bool| paths.iter().any(|path| f(path));
+        if any(|path| path == "engines" || path.starts_with("engines/")) {
+            AssetKind::Engine
+        } else if any(|path| path.contains("plugins")) {
+            AssetKind::Plugin
+        } else if any(|path| path.contains("featurepacks") || path.contains("feature-packs")) {
+            AssetKind::FeaturePack
+        } else if any(|path| path.split('/').any(|part| part == "projects")) {
+            AssetKind::Project
+        } else if any(|path| path == "assets" || path.starts_with("assets/")) {
+            AssetKind::Asset
+        } else {
+            AssetKind::Unknown
+        }
+    }
+
+    /// Get the directory inside the engine where the asset should be extracted
+    ///
+    /// Plugins go to `Engine/Plugins/Marketplace/<Name>` and feature packs to `FeaturePacks`
+    /// of the engine for which they were downloaded. Other kinds are not installed into the engine,
+    /// so `None` is returned for them.
+    pub fn install_subpath(&self, engine_root: &Path) -> Option<PathBuf> {
+        match self.asset_kind() {
+            AssetKind::Plugin => {
+                let name: String = self
+                    .title
+                    .as_deref()
+                    .unwrap_or_default()
+                    .chars()
+                    .filter(|c| c.is_ascii_alphanumeric())
+                    .collect();
+                let name = if name.is_empty() {
+                    self.id.clone()
+                } else {
+                    name
+                };
+                Some(
+                    engine_root
+                        .join("Engine")
+                        .join("Plugins")
+                        .join("Marketplace")
+                        .join(name),
+                )
+            }
+            AssetKind::FeaturePack => Some(engine_root.join("FeaturePacks")),
+            _ => None,
+        }
+    }
+
+    /// Get the technical details with HTML tags and entities stripped, for display as plain text
+    pub fn technical_details_plaintext(&self) -> Option<String> {
+        self.technical_details.as_deref().map(html_to_plaintext)
+    }
+
+    /// Get the image of the given type best suited for displaying at the target width
+    ///
+    /// Picks the smallest image at least `target_width` wide, or the widest one if all of them
+    /// are smaller.
+    pub fn best_image(&self, type_field: &str, target_width: i64) -> Option<&KeyImage> {
+        let images = self
+            .key_images
+            .as_ref()?
+            .iter()
+            .filter(|image| image.type_field == type_field);
+        let (large, small): (Vec<&KeyImage>, Vec<&KeyImage>) =
+            images.partition(|image| image.width >= target_width);
+        match large.into_iter().min_by_key(|image| image.width) {
+            Some(image) => Some(image),
+            None => small.into_iter().max_by_key(|image| image.width),
+        }
+    }
+}
+
+/// Kind of an asset, based on its catalog categories
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    /// Unreal Engine build
+    Engine,
+    /// Code or content plugin, installed into the engine or a project
+    Plugin,
+    /// Feature pack, added to a project
+    FeaturePack,
+    /// Complete project
+    Project,
+    /// Other content, like environments or characters
+    Asset,
+    /// No known category
+    Unknown,
+}
+
+/// Strip tags from basic HTML, turning line breaking tags into newlines
+fn html_to_plaintext(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let end = match rest[start..].find('>') {
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+            Some(end) => start + end,
+        };
+        let tag = rest[start + 1..end]
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match tag.as_str() {
+            "br" | "p" | "div" | "li" | "ul" | "ol" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5"
+            | "h6" => text.push('\n'),
+            _ => {}
+        }
+        if tag == "li" && !rest[start + 1..].starts_with('/') {
+            text.push_str("- ");
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Deserialize the key images, skipping the ones that fail to parse, e.g. due to an invalid url
+fn deserialize_key_images<'de, D>(deserializer: D) -> Result<Option<Vec<KeyImage>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let images: Option<Vec<serde_json::Value>> = Option::deserialize(deserializer)?;
+    Ok(images.map(|images| {
+        images
+            .into_iter()
+            .filter_map(|image| match serde_json::from_value(image) {
+                Ok(image) => Some(image),
+                Err(e) => {
+                    warn!("Skipping invalid key image: {}", e);
+                    None
+                }
+            })
+            .collect()
+    }))
 }
 
 #[allow(missing_docs)]
@@ -153,6 +364,28 @@ pub struct CustomAttribute {
     pub value: String,
 }
 
+impl CustomAttribute {
+    /// Parse the value as a boolean, for boolean and string attributes
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.type_field.to_uppercase().as_str() {
+            "BOOLEAN" | "BOOL" | "STRING" => match self.value.trim().to_lowercase().as_str() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Parse the value as a number, for numeric and string attributes
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.type_field.to_uppercase().as_str() {
+            "INTEGER" | "NUMBER" | "LONG" | "STRING" => self.value.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -173,6 +406,37 @@ pub struct GameToken {
     pub expires_in_seconds: i64,
     pub code: String,
     pub creating_client_id: String,
+    /// Time the token was fetched, set by the client
+    #[serde(default)]
+    pub fetched_at: Option<DateTime<Utc>>,
+}
+
+impl GameToken {
+    /// Get the expiration time of the token fetched at the given time
+    pub fn expires_at(&self, fetched: DateTime<Utc>) -> DateTime<Utc> {
+        fetched + chrono::Duration::seconds(self.expires_in_seconds)
+    }
+
+    /// Check whether the token is expired, tokens with unknown fetch time are treated as expired
+    pub fn is_expired(&self) -> bool {
+        match self.fetched_at {
+            None => true,
+            Some(fetched) => self.expires_at(fetched) <= Utc::now(),
+        }
+    }
+
+    /// Build the URL that logs the browser in with the exchange code and redirects afterwards
+    pub fn exchange_url(&self, redirect_url: &str) -> String {
+        Url::parse_with_params(
+            "https://www.epicgames.com/id/exchange",
+            &[
+                ("exchangeCode", self.code.as_str()),
+                ("redirectUrl", redirect_url),
+            ],
+        )
+        .unwrap()
+        .to_string()
+    }
 }
 
 #[allow(missing_docs)]
@@ -180,3 +444,331 @@ pub struct GameToken {
 pub struct OwnershipToken {
     pub token: String,
 }
+
+impl OwnershipToken {
+    /// Decode the claims from the token payload
+    ///
+    /// The token is a JWT, the signature is not verified.
+    pub fn claims(&self) -> Option<OwnershipClaims> {
+        let payload = self.token.split('.').nth(1)?;
+        let decoded = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+        serde_json::from_slice(&decoded).ok()
+    }
+}
+
+/// Claims of the ownership token
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnershipClaims {
+    /// Subject, usually the account id
+    pub sub: Option<String>,
+    /// Expiration as a unix timestamp
+    pub exp: Option<i64>,
+    /// Issue time as a unix timestamp
+    pub iat: Option<i64>,
+    /// Entitlements granted by the token
+    #[serde(default, alias = "ents")]
+    pub entitlements: Vec<::serde_json::Value>,
+    /// All other claims
+    #[serde(flatten)]
+    pub other: HashMap<String, ::serde_json::Value>,
+}
+
+impl OwnershipClaims {
+    /// Get the expiration time of the token
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        Utc.timestamp_opt(self.exp?, 0).single()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::asset_info::{
+        AssetInfo, AssetKind, Category, CustomAttribute, GameToken, KeyImage, OwnershipToken,
+    };
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use chrono::{Duration, Utc};
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    #[test]
+    fn game_token_exchange_url() {
+        let token = GameToken {
+            code: "abc".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            token.exchange_url("https://www.unrealengine.com/dashboard?lang=en"),
+            "https://www.epicgames.com/id/exchange?exchangeCode=abc&redirectUrl=https%3A%2F%2Fwww.unrealengine.com%2Fdashboard%3Flang%3Den"
+        );
+    }
+
+    #[test]
+    fn game_token_expiry() {
+        let fetched = Utc::now();
+        let mut token = GameToken {
+            expires_in_seconds: 300,
+            ..Default::default()
+        };
+        assert_eq!(token.expires_at(fetched), fetched + Duration::seconds(300));
+        assert!(token.is_expired());
+        token.fetched_at = Some(fetched);
+        assert!(!token.is_expired());
+        token.fetched_at = Some(fetched - Duration::seconds(301));
+        assert!(token.is_expired());
+    }
+
+    #[test]
+    fn ownership_token_claims() {
+        let payload = URL_SAFE_NO_PAD
+            .encode(r#"{"sub":"account","exp":1700000000,"ents":[{"id":"item"}],"iss":"epic"}"#);
+        let token = OwnershipToken {
+            token: format!("eyJhbGciOiJSUzI1NiJ9.{}.signature", payload),
+        };
+        let claims = token.claims().unwrap();
+        assert_eq!(claims.sub, Some("account".to_string()));
+        assert_eq!(claims.entitlements.len(), 1);
+        assert_eq!(claims.expires_at().unwrap().timestamp(), 1700000000);
+        assert_eq!(claims.other.get("iss").unwrap(), "epic");
+    }
+
+    #[test]
+    fn ownership_token_not_jwt() {
+        let token = OwnershipToken {
+            token: "opaque".to_string(),
+        };
+        assert_eq!(token.claims(), None);
+    }
+
+    #[test]
+    fn nested_dlc() {
+        let dlc = |id: &str, dlc_item_list: Vec<AssetInfo>| AssetInfo {
+            id: id.to_string(),
+            dlc_item_list,
+            main_game_item: Box::new(Some(AssetInfo::default())),
+            ..Default::default()
+        };
+        let game = AssetInfo {
+            id: "game".to_string(),
+            dlc_item_list: vec![dlc("a", vec![dlc("a1", vec![])]), dlc("b", vec![])],
+            ..Default::default()
+        };
+        assert!(!game.is_dlc());
+        let all: Vec<&str> = game.all_dlc().iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(all, vec!["a", "a1", "b"]);
+        assert!(game.all_dlc().iter().all(|a| a.is_dlc()));
+    }
+
+    #[test]
+    fn typed_custom_attributes() {
+        let attribute = |type_field: &str, value: &str| CustomAttribute {
+            type_field: type_field.to_string(),
+            value: value.to_string(),
+        };
+        let mut attributes = HashMap::new();
+        attributes.insert("CanRunOffline".to_string(), attribute("STRING", "True"));
+        attributes.insert("Size".to_string(), attribute("STRING", "1024"));
+        attributes.insert("Flag".to_string(), attribute("BOOLEAN", "false"));
+        attributes.insert("Other".to_string(), attribute("JSON", "true"));
+        let info = AssetInfo {
+            custom_attributes: Some(attributes),
+            ..Default::default()
+        };
+        assert_eq!(info.attr("Size"), Some("1024"));
+        assert_eq!(info.attr("Missing"), None);
+        assert_eq!(info.attr_bool("CanRunOffline"), Some(true));
+        assert_eq!(info.attr_bool("Flag"), Some(false));
+        assert_eq!(info.attr_bool("Other"), None);
+        assert_eq!(info.attr_bool("Size"), None);
+        assert_eq!(info.attr_i64("Size"), Some(1024));
+        assert_eq!(info.attr_i64("Flag"), None);
+        assert_eq!(info.can_run_offline(), Some(true));
+        assert_eq!(info.install_size(), None);
+    }
+
+    #[test]
+    fn install_size_and_offline_attributes() {
+        let info = |size: (&str, &str), offline: (&str, &str)| {
+            let mut attributes = HashMap::new();
+            attributes.insert(
+                "InstallSize".to_string(),
+                CustomAttribute {
+                    type_field: size.0.to_string(),
+                    value: size.1.to_string(),
+                },
+            );
+            attributes.insert(
+                "CanRunOffline".to_string(),
+                CustomAttribute {
+                    type_field: offline.0.to_string(),
+                    value: offline.1.to_string(),
+                },
+            );
+            AssetInfo {
+                custom_attributes: Some(attributes),
+                ..Default::default()
+            }
+        };
+        let strings = info(("STRING", "52428800"), ("STRING", "true"));
+        assert_eq!(strings.install_size(), Some(52428800));
+        assert_eq!(strings.can_run_offline(), Some(true));
+        let typed = info(("INTEGER", " 1024 "), ("BOOLEAN", "False"));
+        assert_eq!(typed.install_size(), Some(1024));
+        assert_eq!(typed.can_run_offline(), Some(false));
+        let number = info(("NUMBER", "7"), ("BOOL", "TRUE"));
+        assert_eq!(number.install_size(), Some(7));
+        assert_eq!(number.can_run_offline(), Some(true));
+        for malformed in [
+            info(("STRING", "50 MB"), ("STRING", "yes")),
+            info(("STRING", "-1"), ("STRING", "")),
+            info(("JSON", "1024"), ("JSON", "true")),
+        ] {
+            assert_eq!(malformed.install_size(), None);
+            assert_eq!(malformed.can_run_offline(), None);
+        }
+        assert_eq!(AssetInfo::default().install_size(), None);
+        assert_eq!(AssetInfo::default().can_run_offline(), None);
+    }
+
+    #[test]
+    fn technical_details_plaintext() {
+        let info = AssetInfo {
+            technical_details: Some(
+                "<p><strong>Features:</strong></p><ul><li>Physically-Based &amp; tileable</li>\
+                 <li>4K&nbsp;textures</li></ul><p>Number of Materials: 12<br/>\
+                 Supported Development Platforms: Windows</p>"
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            info.technical_details_plaintext().unwrap(),
+            "Features:\n- Physically-Based & tileable\n- 4K textures\nNumber of Materials: 12\nSupported Development Platforms: Windows"
+        );
+        assert_eq!(AssetInfo::default().technical_details_plaintext(), None);
+    }
+
+    #[test]
+    fn technical_details_plain_text_passthrough() {
+        let info = AssetInfo {
+            technical_details: Some("Plain  text < 5".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            info.technical_details_plaintext().unwrap(),
+            "Plain text < 5"
+        );
+    }
+
+    fn with_categories(paths: &[&str]) -> AssetInfo {
+        AssetInfo {
+            categories: Some(
+                paths
+                    .iter()
+                    .map(|path| Category {
+                        path: path.to_string(),
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn asset_kinds() {
+        assert_eq!(
+            with_categories(&["engines", "engines/ue"]).asset_kind(),
+            AssetKind::Engine
+        );
+        assert_eq!(
+            with_categories(&["assets/codeplugins", "assets"]).asset_kind(),
+            AssetKind::Plugin
+        );
+        assert_eq!(
+            with_categories(&["assets/featurepacks"]).asset_kind(),
+            AssetKind::FeaturePack
+        );
+        assert_eq!(
+            with_categories(&["projects", "assets/projects"]).asset_kind(),
+            AssetKind::Project
+        );
+        assert_eq!(
+            with_categories(&["assets/environments", "assets"]).asset_kind(),
+            AssetKind::Asset
+        );
+        assert_eq!(with_categories(&["games"]).asset_kind(), AssetKind::Unknown);
+        assert_eq!(AssetInfo::default().asset_kind(), AssetKind::Unknown);
+    }
+
+    #[test]
+    fn install_subpath() {
+        let root = Path::new("/opt/UE_5.4");
+        let mut plugin = with_categories(&["assets/codeplugins"]);
+        plugin.title = Some("Easy Multi Save!".to_string());
+        assert_eq!(
+            plugin.install_subpath(root),
+            Some(root.join("Engine/Plugins/Marketplace/EasyMultiSave"))
+        );
+        assert_eq!(
+            with_categories(&["assets/featurepacks"]).install_subpath(root),
+            Some(root.join("FeaturePacks"))
+        );
+        assert_eq!(with_categories(&["projects"]).install_subpath(root), None);
+    }
+
+    #[test]
+    fn best_image() {
+        let image = |type_field: &str, width: i64| KeyImage {
+            type_field: type_field.to_string(),
+            url: format!("https://cdn.example.com/{}-{}.png", type_field, width)
+                .parse()
+                .unwrap(),
+            md5: "".to_string(),
+            width,
+            height: width,
+            size: 0,
+            uploaded_date: Utc::now(),
+        };
+        let info = AssetInfo {
+            key_images: Some(vec![
+                image("Thumbnail", 1920),
+                image("Thumbnail", 284),
+                image("Thumbnail", 576),
+                image("Screenshot", 400),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(info.best_image("Thumbnail", 300).unwrap().width, 576);
+        assert_eq!(info.best_image("Thumbnail", 284).unwrap().width, 284);
+        assert_eq!(info.best_image("Thumbnail", 4000).unwrap().width, 1920);
+        assert_eq!(info.best_image("Screenshot", 100).unwrap().width, 400);
+        assert_eq!(info.best_image("Logo", 100), None);
+        assert_eq!(AssetInfo::default().best_image("Thumbnail", 100), None);
+    }
+
+    #[test]
+    fn invalid_key_images_skipped() {
+        let info: AssetInfo = serde_json::from_str(
+            r#"{
+                "id": "abc",
+                "namespace": "ns",
+                "unsearchable": false,
+                "mainGameItem": null,
+                "keyImages": [
+                    {"type": "Thumbnail", "url": "https://cdn.example.com/a.png", "md5": "", "width": 284, "height": 284, "size": 1, "uploadedDate": "2021-01-01T00:00:00Z"},
+                    {"type": "Thumbnail", "url": "not a url", "md5": "", "width": 576, "height": 576, "size": 1, "uploadedDate": "2021-01-01T00:00:00Z"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let images = info.key_images.unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].width, 284);
+
+        let info: AssetInfo = serde_json::from_str(
+            r#"{"id": "abc", "namespace": "ns", "unsearchable": false, "mainGameItem": null}"#,
+        )
+        .unwrap();
+        assert_eq!(info.key_images, None);
+    }
+}
@@ -1,5 +1,6 @@
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 #[allow(missing_docs)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -13,6 +14,47 @@ pub struct AssetManifest {
 }
 
 impl AssetManifest {
+    /// Merge manifests into one, concatenating the elements
+    ///
+    /// Elements with an already seen `(app_name, hash)` are dropped, the other fields are taken
+    /// from the first manifest that has them set.
+    pub fn merge(manifests: Vec<AssetManifest>) -> AssetManifest {
+        let mut result = AssetManifest::default();
+        let mut seen: HashSet<(String, String)> = HashSet::new();
+        for manifest in manifests {
+            result.platform = result.platform.or(manifest.platform);
+            result.label = result.label.or(manifest.label);
+            result.namespace = result.namespace.or(manifest.namespace);
+            result.item_id = result.item_id.or(manifest.item_id);
+            result.app = result.app.or(manifest.app);
+            for element in manifest.elements {
+                if seen.insert((element.app_name.clone(), element.hash.clone())) {
+                    result.elements.push(element);
+                }
+            }
+        }
+        result
+    }
+
+    /// Get a copy with only the elements of the given build version
+    ///
+    /// Returns `None` when no element has that build version.
+    pub fn for_build(&self, build_version: &str) -> Option<AssetManifest> {
+        let elements: Vec<Element> = self
+            .elements
+            .iter()
+            .filter(|element| element.build_version == build_version)
+            .cloned()
+            .collect();
+        if elements.is_empty() {
+            return None;
+        }
+        Some(AssetManifest {
+            elements,
+            ..self.clone()
+        })
+    }
+
     pub(crate) fn url_csv(&self) -> String {
         let mut res: Vec<String> = Vec::new();
         for elem in &self.elements {
@@ -43,9 +85,116 @@ pub struct Manifest {
     pub query_params: Vec<QueryParam>,
 }
 
+impl Manifest {
+    /// Get the manifest url with the query params appended and percent-encoded
+    pub fn signed_url(&self) -> Url {
+        let mut url = self.uri.clone();
+        if !self.query_params.is_empty() {
+            let mut pairs = url.query_pairs_mut();
+            for query in &self.query_params {
+                pairs.append_pair(&query.name, &query.value);
+            }
+        }
+        url
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QueryParam {
     pub name: String,
     pub value: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::asset_manifest::{AssetManifest, Element, Manifest, QueryParam};
+    use reqwest::Url;
+    use std::str::FromStr;
+
+    fn element(app_name: &str, hash: &str) -> Element {
+        Element {
+            app_name: app_name.to_string(),
+            hash: hash.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_deduplicates_elements() {
+        let live = AssetManifest {
+            elements: vec![element("App", "a"), element("App", "b")],
+            label: Some("Live".to_string()),
+            ..Default::default()
+        };
+        let staging = AssetManifest {
+            elements: vec![
+                element("App", "b"),
+                element("App", "c"),
+                element("Other", "a"),
+            ],
+            label: Some("Staging".to_string()),
+            platform: Some("Windows".to_string()),
+            ..Default::default()
+        };
+        let merged = AssetManifest::merge(vec![live, staging]);
+        let keys: Vec<(&str, &str)> = merged
+            .elements
+            .iter()
+            .map(|e| (e.app_name.as_str(), e.hash.as_str()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![("App", "a"), ("App", "b"), ("App", "c"), ("Other", "a")]
+        );
+        assert_eq!(merged.label, Some("Live".to_string()));
+        assert_eq!(merged.platform, Some("Windows".to_string()));
+    }
+
+    #[test]
+    fn for_build_filters_elements() {
+        let manifest = AssetManifest {
+            elements: vec![
+                Element {
+                    build_version: "1.0.0-1".to_string(),
+                    ..element("App", "a")
+                },
+                Element {
+                    build_version: "1.1.0-2".to_string(),
+                    ..element("App", "b")
+                },
+            ],
+            app: Some("App".to_string()),
+            ..Default::default()
+        };
+        let old = manifest.for_build("1.0.0-1").unwrap();
+        assert_eq!(old.elements.len(), 1);
+        assert_eq!(old.elements[0].hash, "a");
+        assert_eq!(old.app, Some("App".to_string()));
+        assert!(manifest.for_build("0.9.0-1").is_none());
+    }
+
+    #[test]
+    fn signed_url_encodes_params() {
+        let manifest = Manifest {
+            uri: Url::from_str("https://example.com/Builds/app.manifest").unwrap(),
+            query_params: vec![
+                QueryParam {
+                    name: "f_token".to_string(),
+                    value: "exp=1&sig=a/b+c=".to_string(),
+                },
+                QueryParam {
+                    name: "Policy".to_string(),
+                    value: "x".to_string(),
+                },
+            ],
+        };
+        let url = manifest.signed_url();
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/Builds/app.manifest?f_token=exp%3D1%26sig%3Da%2Fb%2Bc%3D&Policy=x"
+        );
+        let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(pairs[0].1, "exp=1&sig=a/b+c=");
+    }
+}
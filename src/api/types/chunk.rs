@@ -1,7 +1,14 @@
 use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 use log::{debug, error};
+use sha1::{Digest, Sha1};
+use std::error::Error;
+use std::fmt;
 use std::io::Read;
 
+const CHUNK_HEADER_MAGIC: u32 = 2986228386;
+
 /// Struct holding data for downloaded chunks
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct Chunk {
@@ -23,53 +30,341 @@ pub struct Chunk {
     pub data: Vec<u8>,
 }
 
+/// Header of a chunk file, parsed without touching the data
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ChunkHeader {
+    /// Header format version
+    pub header_version: u32,
+    /// Size of the header in bytes
+    pub header_size: u32,
+    /// Size of the stored data
+    pub compressed_size: u32,
+    /// Guid of the chunk
+    pub guid: String,
+    /// Chunk rolling hash
+    pub hash: u64,
+    /// Whether the data is zlib compressed
+    pub compressed: bool,
+    /// Chunk sha hash, header version 2 and later
+    pub sha_hash: Option<Vec<u8>>,
+    /// 1 = rolling hash, 2 = sha hash, 3 = both, header version 2 and later
+    pub hash_type: Option<u8>,
+    /// Size of the data after decompression, header version 3 and later
+    pub uncompressed_size: Option<u32>,
+}
+
+/// Error parsing a chunk
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChunkError {
+    /// The buffer starts with something else than the chunk magic
+    BadMagic(u32),
+    /// The buffer ended while reading the named header field
+    Truncated {
+        /// Name of the field being read
+        field: &'static str,
+        /// Offset of the field
+        offset: usize,
+        /// Size of the buffer
+        len: usize,
+    },
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChunkError::BadMagic(magic) => {
+                write!(f, "Bad chunk magic: {:#010x}", magic)
+            }
+            ChunkError::Truncated { field, offset, len } => {
+                write!(
+                    f,
+                    "Chunk truncated reading {} at offset {} of {} bytes",
+                    field, offset, len
+                )
+            }
+        }
+    }
+}
+
+impl Error for ChunkError {}
+
+fn ensure(
+    buffer: &[u8],
+    position: usize,
+    size: usize,
+    field: &'static str,
+) -> Result<(), ChunkError> {
+    if position + size > buffer.len() {
+        Err(ChunkError::Truncated {
+            field,
+            offset: position,
+            len: buffer.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_header(buffer: &[u8]) -> Result<(ChunkHeader, usize), ChunkError> {
+    let mut position: usize = 0;
+    ensure(buffer, position, 4, "magic")?;
+    let magic = crate::api::utils::read_le(buffer, &mut position);
+    if magic != CHUNK_HEADER_MAGIC {
+        return Err(ChunkError::BadMagic(magic));
+    }
+    ensure(buffer, position, 4, "header version")?;
+    let header_version = crate::api::utils::read_le(buffer, &mut position);
+    ensure(buffer, position, 4, "header size")?;
+    let header_size = crate::api::utils::read_le(buffer, &mut position);
+    ensure(buffer, position, 4, "compressed size")?;
+    let compressed_size = crate::api::utils::read_le(buffer, &mut position);
+    ensure(buffer, position, 16, "guid")?;
+    let guid = format!(
+        "{:08x}{:08x}{:08x}{:08x}",
+        crate::api::utils::read_le(buffer, &mut position),
+        crate::api::utils::read_le(buffer, &mut position),
+        crate::api::utils::read_le(buffer, &mut position),
+        crate::api::utils::read_le(buffer, &mut position)
+    );
+    ensure(buffer, position, 8, "hash")?;
+    let hash = crate::api::utils::read_le_64(buffer, &mut position);
+    ensure(buffer, position, 1, "stored as")?;
+    let compressed = !matches!(buffer[position], 0);
+    position += 1;
+    let mut header = ChunkHeader {
+        header_version,
+        header_size,
+        compressed_size,
+        guid,
+        hash,
+        compressed,
+        sha_hash: None,
+        hash_type: None,
+        uncompressed_size: None,
+    };
+    if header_version >= 2 {
+        ensure(buffer, position, 20, "sha hash")?;
+        position += 20;
+        header.sha_hash = Some(buffer[position - 20..position].into());
+        ensure(buffer, position, 1, "hash type")?;
+        header.hash_type = Some(buffer[position]);
+        position += 1;
+    }
+    if header_version >= 3 {
+        ensure(buffer, position, 4, "uncompressed size")?;
+        header.uncompressed_size = Some(crate::api::utils::read_le(buffer, &mut position));
+    }
+    Ok((header, position))
+}
+
 impl Chunk {
+    /// Parse only the header of a chunk, without decompressing the data
+    ///
+    /// Useful to inspect corrupt chunks, the error names the field that could not be read.
+    pub fn inspect_header(buffer: &[u8]) -> Result<ChunkHeader, ChunkError> {
+        parse_header(buffer).map(|(header, _)| header)
+    }
+
     /// Parse chunk from binary vector
     pub fn from_vec(buffer: Vec<u8>) -> Option<Chunk> {
-        let mut position: usize = 0;
-        let magic = crate::api::utils::read_le(&buffer, &mut position);
-        if magic != 2986228386 {
-            error!("No header magic");
-            return None;
-        }
+        let (header, position) = match parse_header(&buffer) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                error!("Unable to parse chunk header: {}", e);
+                return None;
+            }
+        };
         let mut res = Chunk {
-            header_version: crate::api::utils::read_le(&buffer, &mut position),
-            header_size: crate::api::utils::read_le(&buffer, &mut position),
-            compressed_size: crate::api::utils::read_le(&buffer, &mut position),
-            guid: format!(
-                "{:08x}{:08x}{:08x}{:08x}",
-                crate::api::utils::read_le(&buffer, &mut position),
-                crate::api::utils::read_le(&buffer, &mut position),
-                crate::api::utils::read_le(&buffer, &mut position),
-                crate::api::utils::read_le(&buffer, &mut position)
-            ),
-            hash: crate::api::utils::read_le_64(&buffer, &mut position),
-            compressed: !matches!(buffer[position], 0),
-            sha_hash: None,
-            hash_type: None,
-            uncompressed_size: None,
+            header_version: header.header_version,
+            header_size: header.header_size,
+            compressed_size: header.compressed_size,
+            guid: header.guid,
+            hash: header.hash,
+            compressed: header.compressed,
+            sha_hash: header.sha_hash,
+            hash_type: header.hash_type,
+            uncompressed_size: header.uncompressed_size,
             data: vec![],
         };
-        position += 1;
-
-        if res.header_version >= 2 {
-            position += 20;
-            res.sha_hash = Some(buffer[position - 20..position].into());
-            res.hash_type = Some(buffer[position]);
-            position += 1;
-        }
-        if res.header_version >= 3 {
-            res.uncompressed_size = Some(crate::api::utils::read_le(&buffer, &mut position));
-        }
         debug!("Got chunk: {:?}", res);
         res.data = if res.compressed {
             let mut z = ZlibDecoder::new(&buffer[position..]);
             let mut data: Vec<u8> = Vec::new();
-            z.read_to_end(&mut data).unwrap();
+            if let Err(e) = z.read_to_end(&mut data) {
+                error!("Unable to decompress chunk {}: {:?}", res.guid, e);
+                return None;
+            }
             data
         } else {
             buffer[position..].to_vec()
         };
         Some(res)
     }
+
+    /// Verify the data against the hashes stored in the header
+    ///
+    /// The rolling hash is checked when `hash_type` has bit 1 set, or is missing as in version 1
+    /// headers, and the sha hash when bit 2 is set.
+    pub fn verify(&self) -> bool {
+        let hash_type = self.hash_type.unwrap_or(1);
+        if hash_type & 1 == 1 && crate::api::utils::chunk_rolling_hash(&self.data) != self.hash {
+            return false;
+        }
+        if hash_type & 2 == 2 {
+            let mut hasher = Sha1::new();
+            hasher.update(&self.data);
+            match &self.sha_hash {
+                Some(sha) if hasher.finalize().as_slice() == sha.as_slice() => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Serialize the chunk into the binary chunk format
+    ///
+    /// The header is written for `header_version`, the data is compressed when `compressed` is set.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let data = if self.compressed {
+            let mut z = ZlibEncoder::new(Vec::new(), Compression::default());
+            std::io::Write::write_all(&mut z, &self.data).unwrap();
+            z.finish().unwrap()
+        } else {
+            self.data.clone()
+        };
+        let header_size: u32 = match self.header_version {
+            0 | 1 => 41,
+            2 => 62,
+            _ => 66,
+        };
+        let mut result: Vec<u8> = Vec::with_capacity(header_size as usize + data.len());
+        result.extend_from_slice(&CHUNK_HEADER_MAGIC.to_le_bytes());
+        result.extend_from_slice(&self.header_version.to_le_bytes());
+        result.extend_from_slice(&header_size.to_le_bytes());
+        result.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        for i in 0..4 {
+            let part = self
+                .guid
+                .get(i * 8..i * 8 + 8)
+                .and_then(|part| u32::from_str_radix(part, 16).ok())
+                .unwrap_or_default();
+            result.extend_from_slice(&part.to_le_bytes());
+        }
+        result.extend_from_slice(&self.hash.to_le_bytes());
+        result.push(if self.compressed { 1 } else { 0 });
+        if self.header_version >= 2 {
+            let mut sha = self.sha_hash.clone().unwrap_or_default();
+            sha.resize(20, 0);
+            result.extend_from_slice(&sha);
+            result.push(self.hash_type.unwrap_or_default());
+        }
+        if self.header_version >= 3 {
+            result.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        }
+        result.extend_from_slice(&data);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::chunk::{Chunk, ChunkError};
+
+    fn header(version: u32) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(&2986228386u32.to_le_bytes());
+        buffer.extend_from_slice(&version.to_le_bytes());
+        buffer.extend_from_slice(&66u32.to_le_bytes());
+        buffer.extend_from_slice(&3u32.to_le_bytes());
+        for part in [1u32, 2, 3, 4] {
+            buffer.extend_from_slice(&part.to_le_bytes());
+        }
+        buffer.extend_from_slice(&7u64.to_le_bytes());
+        buffer.push(0);
+        if version >= 2 {
+            buffer.extend_from_slice(&[9; 20]);
+            buffer.push(3);
+        }
+        if version >= 3 {
+            buffer.extend_from_slice(&3u32.to_le_bytes());
+        }
+        buffer
+    }
+
+    #[test]
+    fn inspect_header() {
+        let header = Chunk::inspect_header(&header(3)).unwrap();
+        assert_eq!(header.guid, "00000001000000020000000300000004");
+        assert_eq!(header.hash, 7);
+        assert!(!header.compressed);
+        assert_eq!(header.sha_hash, Some(vec![9; 20]));
+        assert_eq!(header.hash_type, Some(3));
+        assert_eq!(header.uncompressed_size, Some(3));
+    }
+
+    #[test]
+    fn to_vec_round_trip() {
+        let mut buffer = header(3);
+        buffer.extend_from_slice(b"abc");
+        let chunk = Chunk::from_vec(buffer.clone()).unwrap();
+        assert_eq!(chunk.to_vec(), buffer);
+
+        let compressed = Chunk {
+            compressed: true,
+            data: b"some chunk data".repeat(10),
+            ..Chunk::from_vec(buffer).unwrap()
+        };
+        let parsed = Chunk::from_vec(compressed.to_vec()).unwrap();
+        assert_eq!(parsed.guid, compressed.guid);
+        assert_eq!(parsed.sha_hash, compressed.sha_hash);
+        assert_eq!(parsed.hash_type, Some(3));
+        assert_eq!(parsed.uncompressed_size, Some(150));
+        assert_eq!(parsed.data, compressed.data);
+        assert_eq!(parsed.to_vec(), compressed.to_vec());
+    }
+
+    #[test]
+    fn verify() {
+        let mut chunk = Chunk {
+            hash_type: Some(1),
+            data: b"abc".to_vec(),
+            hash: 0xbc9d3899098133a7,
+            ..Default::default()
+        };
+        assert!(chunk.verify());
+        chunk.hash_type = Some(3);
+        assert!(!chunk.verify());
+        chunk.sha_hash = Some(vec![
+            0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e, 0x25, 0x71, 0x78, 0x50,
+            0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d,
+        ]);
+        assert!(chunk.verify());
+        chunk.data[0] = b'x';
+        assert!(!chunk.verify());
+    }
+
+    #[test]
+    fn corrupt_compressed_data() {
+        let mut buffer = header(3);
+        buffer[40] = 1;
+        buffer.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef, 0x00, 0x01]);
+        assert_eq!(Chunk::from_vec(buffer), None);
+    }
+
+    #[test]
+    fn inspect_header_errors() {
+        assert_eq!(
+            Chunk::inspect_header(&[0, 0, 0, 0]),
+            Err(ChunkError::BadMagic(0))
+        );
+        let buffer = header(2);
+        assert_eq!(
+            Chunk::inspect_header(&buffer[..50]),
+            Err(ChunkError::Truncated {
+                field: "sha hash",
+                offset: 41,
+                len: 50
+            })
+        );
+    }
 }
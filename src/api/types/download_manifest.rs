@@ -1,3 +1,4 @@
+use crate::api::error::EpicAPIError;
 use flate2::read::ZlibDecoder;
 use flate2::write::ZlibEncoder;
 use flate2::Compression;
@@ -6,13 +7,18 @@ use reqwest::Url;
 use serde::{de, Deserialize, Serialize};
 use sha1::{Digest, Sha1};
 use std::borrow::BorrowMut;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::fmt::Write;
+use std::io;
 use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 use std::str::FromStr;
 
+/// Custom fields describing where the manifest was downloaded from
+const DOWNLOAD_FIELDS: &[&str] = &["SourceURL", "BaseUrl", "DownloadedManifestHash"];
+
 #[allow(missing_docs)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -149,6 +155,22 @@ impl DownloadManifest {
         }
     }
 
+    /// Get file data dir based on the manifest version, used when `b_is_file_data` is set
+    ///
+    /// Follows `ManifestVersionHelpers::GetFileSubdir` of BuildPatchServices, which switches to
+    /// `FilesV2` with the `DataFileRenames` feature level (3) and to `FilesV3` with
+    /// `StoresChunkDataShaHashes` (12). Files are then stored as
+    /// `{dir}/{group:02}/{Hash:016X}_{SHA}.file`.
+    fn file_data_dir(version: u128) -> &'static str {
+        if version >= 12 {
+            "FilesV3"
+        } else if version >= 3 {
+            "FilesV2"
+        } else {
+            "Files"
+        }
+    }
+
     pub(crate) fn set_custom_field(&mut self, key: String, value: String) {
         if let Some(fields) = self.custom_fields.as_mut() {
             fields.insert(key, value);
@@ -195,18 +217,34 @@ impl DownloadManifest {
                 }
                 Some(group) => group,
             };
-            result.insert(
-                guid.clone(),
-                Url::parse(&format!(
+            // File data manifests store whole files named by their hash and SHA instead of chunks
+            let link = if self.b_is_file_data {
+                let sha = match self.chunk_sha(&guid) {
+                    None => {
+                        error!("File data {} has no SHA, unable to build its link", guid);
+                        continue;
+                    }
+                    Some(sha) => sha.to_uppercase(),
+                };
+                format!(
+                    "{}/{}/{:02}/{:016X}_{}.file",
+                    url,
+                    DownloadManifest::file_data_dir(self.manifest_file_version),
+                    group_num,
+                    hash,
+                    sha
+                )
+            } else {
+                format!(
                     "{}/{}/{:02}/{:016X}_{}.chunk",
                     url,
                     chunk_dir,
                     group_num,
                     hash,
                     guid.to_uppercase()
-                ))
-                .unwrap(),
-            );
+                )
+            };
+            result.insert(guid.clone(), Url::parse(&link).unwrap());
         }
         Some(result)
     }
@@ -222,6 +260,7 @@ impl DownloadManifest {
                 FileManifestList {
                     filename: file.filename,
                     file_hash: file.file_hash,
+                    install_tags: file.install_tags,
                     file_chunk_parts: {
                         let mut temp: Vec<FileChunkPart> = Vec::new();
                         for part in file.file_chunk_parts {
@@ -245,6 +284,52 @@ impl DownloadManifest {
         result
     }
 
+    /// Get all install tags used by the files
+    pub fn install_tags(&self) -> HashSet<String> {
+        self.file_manifest_list
+            .iter()
+            .flat_map(|file| file.install_tags.iter().cloned())
+            .collect()
+    }
+
+    /// Get the files to install for the selected tags
+    ///
+    /// Files without install tags are always included, tagged files only when one of their tags
+    /// is selected.
+    pub fn files_with_tags(&self, tags: &[String]) -> Vec<&FileManifestList> {
+        self.file_manifest_list
+            .iter()
+            .filter(|file| {
+                file.install_tags.is_empty() || file.install_tags.iter().any(|t| tags.contains(t))
+            })
+            .collect()
+    }
+
+    /// Get the hex encoded SHA-1 of a chunk, the guid is matched case insensitively
+    pub fn chunk_sha(&self, guid: &str) -> Option<&str> {
+        let list = self.chunk_sha_list.as_ref()?;
+        list.get(guid)
+            .or_else(|| {
+                list.iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(guid))
+                    .map(|(_, sha)| sha)
+            })
+            .map(|sha| sha.as_str())
+    }
+
+    /// Get the chunks needed to update from the installed manifest to this one
+    ///
+    /// Returns the GUIDs and download links of chunks that the installed manifest does not have.
+    pub fn chunks_to_fetch(&self, installed: &DownloadManifest) -> Vec<(String, Url)> {
+        let links = self.download_links().unwrap_or_default();
+        let mut result: Vec<(String, Url)> = links
+            .into_iter()
+            .filter(|(guid, _)| !installed.chunk_hash_list.contains_key(guid))
+            .collect();
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+        result
+    }
+
     /// Get total size of chunks in the manifest
     pub fn total_download_size(&self) -> u128 {
         let mut total: u128 = 0;
@@ -263,6 +348,41 @@ impl DownloadManifest {
         total
     }
 
+    /// Get the files as `(filename, size, file_hash)` rows sorted by filename
+    pub fn file_table(&self) -> Vec<(String, u128, String)> {
+        let mut table: Vec<(String, u128, String)> = self
+            .file_manifest_list
+            .iter()
+            .map(|file| (file.filename.clone(), file.size(), file.file_hash.clone()))
+            .collect();
+        table.sort_by(|a, b| a.0.cmp(&b.0));
+        table
+    }
+
+    /// Get the file list as a JSON array, without the chunk details
+    ///
+    /// Every file is an object with the `filename`, the `size` in bytes and the `hash` as stored
+    /// in the manifest, sorted by filename, e.g. `[{"filename":"a.ini","size":10,"hash":"..."}]`.
+    /// The format is kept stable for downstream tools.
+    pub fn files_json(&self) -> String {
+        #[derive(Serialize)]
+        struct FileEntry {
+            filename: String,
+            size: u128,
+            hash: String,
+        }
+        let entries: Vec<FileEntry> = self
+            .file_table()
+            .into_iter()
+            .map(|(filename, size, hash)| FileEntry {
+                filename,
+                size,
+                hash,
+            })
+            .collect();
+        serde_json::to_string(&entries).unwrap_or_else(|_| "[]".to_string())
+    }
+
     /// Parse DownloadManifest from binary data or Json
     pub fn parse(data: Vec<u8>) -> Option<DownloadManifest> {
         debug!("Attempting to parse download manifest from binary data");
@@ -290,8 +410,166 @@ impl DownloadManifest {
         }
     }
 
+    /// Check whether both manifests describe the same content
+    ///
+    /// Unlike the derived `PartialEq` this ignores the custom fields, which differ between
+    /// copies of the same manifest downloaded from different URLs.
+    /// The order of the files does not matter either.
+    pub fn same_content(&self, other: &Self) -> bool {
+        let content = |manifest: &DownloadManifest| {
+            let mut content = DownloadManifest {
+                custom_fields: None,
+                ..manifest.clone()
+            };
+            content
+                .file_manifest_list
+                .sort_by(|a, b| a.filename.cmp(&b.filename));
+            content
+        };
+        content(self) == content(other)
+    }
+
+    /// Get a copy of the manifest without the custom fields set when downloading it
+    ///
+    /// Removes `SourceURL`, `BaseUrl` and `DownloadedManifestHash`, which change with every download,
+    /// while fields like `CatalogItemId` are kept. Useful for a stable representation on disk.
+    pub fn without_download_fields(&self) -> DownloadManifest {
+        let mut manifest = self.clone();
+        if let Some(fields) = manifest.custom_fields.as_mut() {
+            for key in DOWNLOAD_FIELDS {
+                fields.remove(*key);
+            }
+            if fields.is_empty() {
+                manifest.custom_fields = None;
+            }
+        }
+        manifest
+    }
+
+    /// Get the data needed to launch the installed asset
+    pub fn launch_info(&self) -> LaunchInfo {
+        LaunchInfo {
+            executable: self.launch_exe_string.clone(),
+            arguments: self.launch_command.clone(),
+            prerequisite: self.prerequisite(),
+        }
+    }
+
+    /// Check whether the manifest has a prerequisite installer to run after installation
+    pub fn has_prerequisites(&self) -> bool {
+        !self.prereq_path.is_empty()
+    }
+
+    /// Get the prerequisite installer, like the VC++ or DirectX redistributables
+    pub fn prerequisite(&self) -> Option<Prerequisite> {
+        if !self.has_prerequisites() {
+            return None;
+        }
+        Some(Prerequisite {
+            ids: self.prereq_ids.clone().unwrap_or_default(),
+            name: self.prereq_name.clone(),
+            path: self.prereq_path.clone(),
+            args: self.prereq_args.clone(),
+        })
+    }
+
+    /// Get the action to run when uninstalling the asset
+    pub fn uninstall_info(&self) -> Option<UninstallAction> {
+        match &self.uninstall_action_path {
+            Some(path) if !path.is_empty() => Some(UninstallAction {
+                path: path.clone(),
+                args: self.uninstall_action_args.clone().unwrap_or_default(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Get a one line summary of the manifest for display
+    ///
+    /// Contains the app name, build version, number of files, total size and number of chunks.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} {}: {} files, {} ({} download), {} chunks",
+            self.app_name_string,
+            self.build_version_string,
+            self.file_manifest_list.len(),
+            crate::api::utils::human_bytes(self.total_size()),
+            crate::api::utils::human_bytes(self.total_download_size()),
+            self.chunk_hash_list.len()
+        )
+    }
+
+    /// Read and parse a DownloadManifest stored in a file, binary or Json
+    ///
+    /// Returns `Ok(None)` when the file was read but could not be parsed.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Option<DownloadManifest>> {
+        Ok(DownloadManifest::parse(std::fs::read(path)?))
+    }
+
+    /// Write the manifest to a file in the binary format
+    pub fn to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_vec())
+    }
+
     /// Creates the structure from binary data
-    pub fn from_vec(mut buffer: Vec<u8>) -> Option<DownloadManifest> {
+    pub fn from_vec(buffer: Vec<u8>) -> Option<DownloadManifest> {
+        DownloadManifest::from_reader(buffer.as_slice()).unwrap_or(None)
+    }
+
+    /// Read and parse a binary DownloadManifest
+    ///
+    /// Compressed manifests are decompressed while reading, so the compressed data is never
+    /// buffered as a whole. Returns `Ok(None)` when the data is not a valid binary manifest.
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Option<DownloadManifest>> {
+        let mut header = [0u8; 41];
+        match reader.read_exact(&mut header) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                error!("Manifest header is truncated");
+                return Ok(None);
+            }
+            result => result?,
+        }
+        let mut position: usize = 0;
+
+        // Reading Header
+        let magic = crate::api::utils::read_le(&header, &mut position);
+        if magic != 1153351692 {
+            error!("No header magic");
+            return Ok(None);
+        }
+        let header_size = crate::api::utils::read_le(&header, &mut position);
+        debug!("Header size: {}", header_size);
+        let _size_uncompressed = crate::api::utils::read_le(&header, &mut position);
+        let _size_compressed = crate::api::utils::read_le(&header, &mut position);
+        position += 20;
+        let sha_hash: [u8; 20] = header[position - 20..position].try_into().unwrap();
+        let compressed = !matches!(header[position], 0);
+
+        let mut data: Vec<u8> = Vec::new();
+        if compressed {
+            debug!("Uncompressing");
+            match ZlibDecoder::new(reader).read_to_end(&mut data) {
+                Err(e)
+                    if e.kind() == io::ErrorKind::InvalidInput
+                        || e.kind() == io::ErrorKind::InvalidData =>
+                {
+                    error!("Unable to decompress the manifest: {}", e);
+                    return Ok(None);
+                }
+                result => result?,
+            };
+            if !crate::api::utils::do_vecs_match(sha_hash.as_ref(), &Sha1::digest(&data)) {
+                error!("The extracted hash does not match");
+                return Ok(None);
+            }
+        } else {
+            reader.read_to_end(&mut data)?;
+        }
+        Ok(DownloadManifest::from_body(data))
+    }
+
+    /// Parse the manifest data following the header, uncompressed
+    fn from_body(buffer: Vec<u8>) -> Option<DownloadManifest> {
         let mut res = DownloadManifest {
             manifest_file_version: 0,
             b_is_file_data: false,
@@ -316,43 +594,6 @@ impl DownloadManifest {
 
         let mut position: usize = 0;
 
-        // Reading Header
-        let magic = crate::api::utils::read_le(&buffer, &mut position);
-        if magic != 1153351692 {
-            error!("No header magic");
-            return None;
-        }
-        let mut header_size = crate::api::utils::read_le(&buffer, &mut position);
-        debug!("Header size: {}", header_size);
-        let _size_uncompressed = crate::api::utils::read_le(&buffer, &mut position);
-        let _size_compressed = crate::api::utils::read_le(&buffer, &mut position);
-        position += 20;
-        let sha_hash: [u8; 20] = buffer[position - 20..position].try_into().unwrap();
-        let compressed = !matches!(buffer[position], 0);
-        position += 1;
-        let _version = crate::api::utils::read_le(&buffer, &mut position);
-
-        buffer = if compressed {
-            debug!("Uncompressing");
-            let mut z = ZlibDecoder::new(&buffer[position..]);
-            let mut data: Vec<u8> = Vec::new();
-            z.read_to_end(&mut data).unwrap();
-            if !crate::api::utils::do_vecs_match(sha_hash.as_ref(), &Sha1::digest(&data)) {
-                error!("The extracted hash does not match");
-                return None;
-            }
-            position = 0;
-            header_size = 0;
-            data
-        } else {
-            buffer
-        };
-
-        debug!(
-            "Download manifest header read length(needs to match {}): {}",
-            header_size, position
-        );
-
         // Manifest Meta
 
         let meta_size = crate::api::utils::read_le(&buffer, &mut position);
@@ -409,8 +650,7 @@ impl DownloadManifest {
 
         debug!(
             "Manifest metadata read length(needs to match {}): {}",
-            meta_size,
-            position - header_size as usize
+            meta_size, position
         );
 
         // Chunks
@@ -491,7 +731,7 @@ impl DownloadManifest {
         debug!(
             "Chunks read length(needs to match {}): {}",
             chunk_size,
-            position - meta_size as usize - header_size as usize
+            position - meta_size as usize
         );
 
         // File Manifest
@@ -617,13 +857,14 @@ impl DownloadManifest {
                     output
                 }),
                 file_chunk_parts: chunks,
+                install_tags: file.install_tags.clone(),
             })
         }
 
         debug!(
             "File Manifests read length(needs to match {}): {}",
             filemanifest_size,
-            position - meta_size as usize - header_size as usize - chunk_size as usize
+            position - meta_size as usize - chunk_size as usize
         );
 
         // Custom Fields
@@ -656,16 +897,11 @@ impl DownloadManifest {
         debug!(
             "Custom fields read length(needs to match {}): {}",
             size,
-            position
-                - meta_size as usize
-                - header_size as usize
-                - chunk_size as usize
-                - filemanifest_size as usize
+            position - meta_size as usize - chunk_size as usize - filemanifest_size as usize
         );
 
         if position
             - meta_size as usize
-            - header_size as usize
             - chunk_size as usize
             - filemanifest_size as usize
             - size as usize
@@ -684,11 +920,15 @@ impl DownloadManifest {
         let mut data: Vec<u8> = Vec::new();
         let mut meta: Vec<u8> = Vec::new();
         // Data version
-        meta.push(if self.build_version_string.is_empty() {
-            0
-        } else {
-            1
-        });
+        let data_version: u8 =
+            if self.uninstall_action_path.is_some() || self.uninstall_action_args.is_some() {
+                2
+            } else if self.build_version_string.is_empty() {
+                0
+            } else {
+                1
+            };
+        meta.push(data_version);
         // Feature level
         match u32::try_from(self.manifest_file_version) {
             Ok(version) => meta.append(version.to_le_bytes().to_vec().borrow_mut()),
@@ -733,11 +973,25 @@ impl DownloadManifest {
 
         meta.append(crate::api::utils::write_fstring(self.prereq_args.clone()).borrow_mut());
 
-        if !self.build_version_string.is_empty() {
+        if data_version >= 1 {
             meta.append(
                 crate::api::utils::write_fstring(self.build_version_string.clone()).borrow_mut(),
             );
         }
+        if data_version >= 2 {
+            meta.append(
+                crate::api::utils::write_fstring(
+                    self.uninstall_action_path.clone().unwrap_or_default(),
+                )
+                .borrow_mut(),
+            );
+            meta.append(
+                crate::api::utils::write_fstring(
+                    self.uninstall_action_args.clone().unwrap_or_default(),
+                )
+                .borrow_mut(),
+            );
+        }
         // Meta Size
         data.append(
             ((meta.len() + 4) as u32)
@@ -869,13 +1123,19 @@ impl DownloadManifest {
 
         // flags
         // TODO: Figure out what Epic puts in theirs
-        files.resize(self.file_manifest_list.len(), 0);
+        files.resize(files.len() + self.file_manifest_list.len(), 0);
 
         // install tags
-        // TODO: Figure out what Epic puts in theirs
-        for _ in &self.file_manifest_list {
-            files.append(0u32.to_le_bytes().to_vec().borrow_mut());
-            // files.append(crate::api::utils::write_fstring("".to_string()).borrow_mut());
+        for file in &self.file_manifest_list {
+            files.append(
+                (file.install_tags.len() as u32)
+                    .to_le_bytes()
+                    .to_vec()
+                    .borrow_mut(),
+            );
+            for tag in &file.install_tags {
+                files.append(crate::api::utils::write_fstring(tag.clone()).borrow_mut());
+            }
         }
 
         // File Chunks
@@ -991,6 +1251,91 @@ impl DownloadManifest {
     }
 }
 
+impl fmt::Display for DownloadManifest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.summary())
+    }
+}
+
+/// Executable and arguments to launch an installed asset
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct LaunchInfo {
+    /// Executable path relative to the install directory
+    pub executable: String,
+    /// Arguments passed to the executable
+    pub arguments: String,
+    /// Prerequisite installer to run before the first launch
+    pub prerequisite: Option<Prerequisite>,
+}
+
+impl LaunchInfo {
+    /// Get the executable path inside the install directory
+    pub fn executable_path(&self, install_dir: &Path) -> PathBuf {
+        join_manifest_path(install_dir, &self.executable)
+    }
+}
+
+/// Prerequisite installer shipped with an asset
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Prerequisite {
+    /// Ids of the prerequisites, used to tell whether they are already installed
+    pub ids: Vec<String>,
+    /// Display name
+    pub name: String,
+    /// Installer path relative to the install directory
+    pub path: String,
+    /// Arguments passed to the installer
+    pub args: String,
+}
+
+impl Prerequisite {
+    /// Get the installer path inside the install directory
+    pub fn installer_path(&self, install_dir: &Path) -> PathBuf {
+        join_manifest_path(install_dir, &self.path)
+    }
+}
+
+/// Executable to run when uninstalling an asset
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct UninstallAction {
+    /// Executable path relative to the install directory
+    pub path: String,
+    /// Arguments passed to the executable
+    pub args: String,
+}
+
+impl UninstallAction {
+    /// Get the executable path inside the install directory
+    pub fn executable_path(&self, install_dir: &Path) -> PathBuf {
+        join_manifest_path(install_dir, &self.path)
+    }
+}
+
+/// Join a manifest path, which may use backslashes, to the directory
+fn join_manifest_path(dir: &Path, path: &str) -> PathBuf {
+    path.split(['/', '\\'])
+        .filter(|part| !part.is_empty())
+        .fold(dir.to_path_buf(), |result, part| result.join(part))
+}
+
+/// Join a manifest path to the directory, rejecting paths that would leave it
+///
+/// Fails with `EpicAPIError::InvalidParams` for `..` components, rooted paths and drive prefixes.
+pub(crate) fn join_manifest_path_checked(dir: &Path, path: &str) -> Result<PathBuf, EpicAPIError> {
+    let bytes = path.as_bytes();
+    let drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    let escapes = path.split(['/', '\\']).any(|part| {
+        Path::new(part)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    });
+    if drive || escapes || path.starts_with(['/', '\\']) {
+        error!("Manifest path {} leaves the target directory", path);
+        return Err(EpicAPIError::InvalidParams);
+    }
+    Ok(join_manifest_path(dir, path))
+}
+
 #[allow(missing_docs)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -999,6 +1344,8 @@ pub struct FileManifestList {
     #[serde(deserialize_with = "deserialize_epic_hash")]
     pub file_hash: String,
     pub file_chunk_parts: Vec<FileChunkPart>,
+    #[serde(default)]
+    pub install_tags: Vec<String>,
 }
 
 impl FileManifestList {
@@ -1057,3 +1404,385 @@ struct BinaryChunkInfo {
     window_size: u32,
     file_size: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::download_manifest::{DownloadManifest, FileChunkPart, FileManifestList};
+    use std::path::Path;
+
+    fn manifest(chunks: &[(&str, u128)]) -> DownloadManifest {
+        let mut manifest = DownloadManifest {
+            manifest_file_version: 21,
+            ..Default::default()
+        };
+        for (guid, hash) in chunks {
+            manifest.chunk_hash_list.insert(guid.to_string(), *hash);
+            manifest.data_group_list.insert(guid.to_string(), 1);
+        }
+        manifest.set_custom_field(
+            "SourceURL".to_string(),
+            "https://download.epicgames.com/Builds/Org/o-abc/123/default".to_string(),
+        );
+        manifest
+    }
+
+    #[test]
+    fn chunks_to_fetch_new_chunk() {
+        let installed = manifest(&[("aa", 1), ("bb", 2)]);
+        let update = manifest(&[("aa", 1), ("bb", 2), ("cc", 3)]);
+        let chunks = update.chunks_to_fetch(&installed);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].0, "cc");
+        assert_eq!(
+            chunks[0].1.as_str(),
+            "https://download.epicgames.com/Builds/Org/o-abc/123/default/ChunksV4/01/0000000000000003_CC.chunk"
+        );
+        assert!(installed.chunks_to_fetch(&update).is_empty());
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let mut manifest = manifest(&[("0123456789abcdef0123456789abcdef", 7)]);
+        manifest.app_name_string = "App".to_string();
+        manifest.build_version_string = "1.0.0-1".to_string();
+        manifest
+            .chunk_filesize_list
+            .insert("0123456789abcdef0123456789abcdef".to_string(), 10);
+        manifest.chunk_sha_list = Some(
+            [(
+                "0123456789abcdef0123456789abcdef".to_string(),
+                "00112233445566778899aabbccddeeff00112233".to_string(),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        manifest.file_manifest_list.push(FileManifestList {
+            filename: "Content/file.bin".to_string(),
+            file_hash: "00112233445566778899aabbccddeeff00112233".to_string(),
+            file_chunk_parts: vec![FileChunkPart {
+                guid: "0123456789abcdef0123456789abcdef".to_string(),
+                link: None,
+                offset: 0,
+                size: 10,
+            }],
+            install_tags: vec!["Content".to_string()],
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("manifest.manifest");
+        manifest.to_file(&path).unwrap();
+        let read = DownloadManifest::from_file(&path).unwrap().unwrap();
+        assert_eq!(read.app_name_string, "App");
+        assert_eq!(read.build_version_string, "1.0.0-1");
+        assert_eq!(read.chunk_hash_list, manifest.chunk_hash_list);
+        assert_eq!(read.file_manifest_list, manifest.file_manifest_list);
+
+        std::fs::write(&path, b"garbage").unwrap();
+        assert_eq!(DownloadManifest::from_file(&path).unwrap(), None);
+        assert!(DownloadManifest::from_file(dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn summary() {
+        let mut manifest = manifest(&[("aa", 1), ("bb", 2)]);
+        manifest.app_name_string = "App".to_string();
+        manifest.build_version_string = "1.0.0-1".to_string();
+        manifest.chunk_filesize_list.insert("aa".to_string(), 1024);
+        manifest.chunk_filesize_list.insert("bb".to_string(), 1024);
+        manifest.file_manifest_list.push(FileManifestList {
+            filename: "file.bin".to_string(),
+            file_hash: "".to_string(),
+            file_chunk_parts: vec![FileChunkPart {
+                guid: "aa".to_string(),
+                link: None,
+                offset: 0,
+                size: 3 * 1024 * 1024 * 1024,
+            }],
+            install_tags: vec![],
+        });
+        assert_eq!(
+            manifest.summary(),
+            "App 1.0.0-1: 1 files, 3.00 GiB (2.00 KiB download), 2 chunks"
+        );
+        assert_eq!(manifest.to_string(), manifest.summary());
+    }
+
+    #[test]
+    fn without_download_fields() {
+        let mut manifest = manifest(&[("aa", 1)]);
+        manifest.set_custom_field("BaseUrl".to_string(), "https://a,https://b".to_string());
+        manifest.set_custom_field("DownloadedManifestHash".to_string(), "abc".to_string());
+        manifest.set_custom_field("CatalogItemId".to_string(), "item".to_string());
+        let stripped = manifest.without_download_fields();
+        assert_eq!(stripped.custom_field("SourceURL"), None);
+        assert_eq!(stripped.custom_field("BaseUrl"), None);
+        assert_eq!(stripped.custom_field("DownloadedManifestHash"), None);
+        assert_eq!(
+            stripped.custom_field("CatalogItemId"),
+            Some("item".to_string())
+        );
+        assert!(stripped.same_content(&manifest));
+
+        let bare = self::manifest(&[("aa", 1)]).without_download_fields();
+        assert_eq!(bare.custom_fields, None);
+    }
+
+    #[test]
+    fn same_content() {
+        let file = |filename: &str| FileManifestList {
+            filename: filename.to_string(),
+            file_hash: "00".to_string(),
+            ..Default::default()
+        };
+        let mut first = manifest(&[("aa", 1), ("bb", 2)]);
+        first.file_manifest_list = vec![file("a.bin"), file("b.bin")];
+        let mut second = first.clone();
+        second.set_custom_field("SourceURL".to_string(), "https://mirror".to_string());
+        assert!(first.same_content(&second));
+        assert!(first.same_content(&first));
+
+        second.file_manifest_list.reverse();
+        assert!(first.same_content(&second));
+        assert!(second.same_content(&first));
+
+        let mut changed = second.clone();
+        changed.file_manifest_list[0].file_hash = "01".to_string();
+        assert!(!first.same_content(&changed));
+        let mut changed = second.clone();
+        changed.chunk_hash_list.insert("bb".to_string(), 3);
+        assert!(!first.same_content(&changed));
+        let mut changed = second;
+        changed.build_version_string = "2.0".to_string();
+        assert!(!first.same_content(&changed));
+    }
+
+    #[test]
+    fn install_tags() {
+        let mut manifest = manifest(&[]);
+        for (filename, tags) in [
+            ("base.bin", vec![]),
+            ("hd.bin", vec!["HighRes"]),
+            ("docs.bin", vec!["Docs", "HighRes"]),
+        ] {
+            manifest.file_manifest_list.push(FileManifestList {
+                filename: filename.to_string(),
+                install_tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            });
+        }
+        let mut tags: Vec<String> = manifest.install_tags().into_iter().collect();
+        tags.sort();
+        assert_eq!(tags, vec!["Docs", "HighRes"]);
+        let selected: Vec<&str> = manifest
+            .files_with_tags(&["Docs".to_string()])
+            .iter()
+            .map(|f| f.filename.as_str())
+            .collect();
+        assert_eq!(selected, vec!["base.bin", "docs.bin"]);
+        assert_eq!(manifest.files_with_tags(&[]).len(), 1);
+    }
+
+    #[test]
+    fn download_links_layouts() {
+        let mut manifest = manifest(&[("0123456789abcdef0123456789abcdef", 3)]);
+        assert_eq!(
+            manifest.download_links().unwrap()["0123456789abcdef0123456789abcdef"].as_str(),
+            "https://download.epicgames.com/Builds/Org/o-abc/123/default/ChunksV4/01/0000000000000003_0123456789ABCDEF0123456789ABCDEF.chunk"
+        );
+        manifest.b_is_file_data = true;
+        manifest.manifest_file_version = 3;
+        assert!(manifest.download_links().unwrap().is_empty());
+        manifest.chunk_sha_list = Some(
+            [(
+                "0123456789abcdef0123456789abcdef".to_string(),
+                "00112233445566778899aabbccddeeff00112233".to_string(),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        assert_eq!(
+            manifest.download_links().unwrap()["0123456789abcdef0123456789abcdef"].as_str(),
+            "https://download.epicgames.com/Builds/Org/o-abc/123/default/FilesV2/01/0000000000000003_00112233445566778899AABBCCDDEEFF00112233.file"
+        );
+        manifest.manifest_file_version = 12;
+        assert_eq!(
+            manifest.download_links().unwrap()["0123456789abcdef0123456789abcdef"].as_str(),
+            "https://download.epicgames.com/Builds/Org/o-abc/123/default/FilesV3/01/0000000000000003_00112233445566778899AABBCCDDEEFF00112233.file"
+        );
+        manifest.manifest_file_version = 2;
+        assert!(
+            manifest.download_links().unwrap()["0123456789abcdef0123456789abcdef"]
+                .as_str()
+                .contains("/Files/01/")
+        );
+    }
+
+    #[test]
+    fn launch_info() {
+        let mut manifest = manifest(&[]);
+        manifest.launch_exe_string = "Game\\Binaries\\Win64\\Game.exe".to_string();
+        manifest.launch_command = "-nosplash".to_string();
+        let info = manifest.launch_info();
+        assert_eq!(info.arguments, "-nosplash");
+        assert_eq!(
+            info.executable_path(Path::new("/games/App")),
+            Path::new("/games/App/Game/Binaries/Win64/Game.exe")
+        );
+        assert_eq!(info.prerequisite, None);
+        manifest.prereq_path = "Redist/setup.exe".to_string();
+        assert_eq!(
+            manifest
+                .launch_info()
+                .prerequisite
+                .unwrap()
+                .installer_path(Path::new("/games/App")),
+            Path::new("/games/App/Redist/setup.exe")
+        );
+    }
+
+    #[test]
+    fn prerequisites() {
+        let mut manifest = manifest(&[]);
+        assert!(!manifest.has_prerequisites());
+        assert_eq!(manifest.prerequisite(), None);
+        manifest.prereq_ids = Some(vec!["VCRedist".to_string()]);
+        manifest.prereq_name = "Visual C++ Runtime".to_string();
+        manifest.prereq_path = "Redist\\vc_redist.x64.exe".to_string();
+        manifest.prereq_args = "/quiet".to_string();
+        assert!(manifest.has_prerequisites());
+        let prerequisite = manifest.prerequisite().unwrap();
+        assert_eq!(prerequisite.ids, vec!["VCRedist"]);
+        assert_eq!(prerequisite.name, "Visual C++ Runtime");
+        assert_eq!(prerequisite.args, "/quiet");
+        assert_eq!(
+            prerequisite.installer_path(Path::new("/games/App")),
+            Path::new("/games/App/Redist/vc_redist.x64.exe")
+        );
+    }
+
+    #[test]
+    fn uninstall_info() {
+        let mut manifest = manifest(&[]);
+        assert_eq!(manifest.uninstall_info(), None);
+        manifest.uninstall_action_path = Some("".to_string());
+        assert_eq!(manifest.uninstall_info(), None);
+        manifest.uninstall_action_path = Some("Tools/uninstall.exe".to_string());
+        manifest.uninstall_action_args = Some("/S".to_string());
+        let action = manifest.uninstall_info().unwrap();
+        assert_eq!(action.args, "/S");
+        assert_eq!(
+            action.executable_path(Path::new("/games/App")),
+            Path::new("/games/App/Tools/uninstall.exe")
+        );
+    }
+
+    #[test]
+    fn uninstall_action_round_trip() {
+        let mut manifest = manifest(&[("0123456789abcdef0123456789abcdef", 7)]);
+        manifest.app_name_string = "App".to_string();
+        manifest.build_version_string = "1.0.0-1".to_string();
+        manifest.chunk_sha_list = Some(
+            [(
+                "0123456789abcdef0123456789abcdef".to_string(),
+                "00112233445566778899aabbccddeeff00112233".to_string(),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        manifest
+            .chunk_filesize_list
+            .insert("0123456789abcdef0123456789abcdef".to_string(), 10);
+        manifest.uninstall_action_path = Some("Tools/uninstall.exe".to_string());
+        manifest.uninstall_action_args = Some("/S".to_string());
+        let read = DownloadManifest::parse(manifest.to_vec()).unwrap();
+        assert_eq!(read.build_version_string, "1.0.0-1");
+        assert_eq!(read.uninstall_action_path, manifest.uninstall_action_path);
+        assert_eq!(read.uninstall_action_args, manifest.uninstall_action_args);
+        assert_eq!(read.uninstall_info(), manifest.uninstall_info());
+
+        manifest.uninstall_action_path = None;
+        manifest.uninstall_action_args = None;
+        let read = DownloadManifest::parse(manifest.to_vec()).unwrap();
+        assert_eq!(read.uninstall_action_path, None);
+        assert_eq!(read.build_version_string, "1.0.0-1");
+    }
+
+    #[test]
+    fn from_reader() {
+        let mut manifest = manifest(&[("0123456789abcdef0123456789abcdef", 7)]);
+        manifest.app_name_string = "App".to_string();
+        manifest.chunk_sha_list = Some(
+            [(
+                "0123456789abcdef0123456789abcdef".to_string(),
+                "00112233445566778899aabbccddeeff00112233".to_string(),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        manifest
+            .chunk_filesize_list
+            .insert("0123456789abcdef0123456789abcdef".to_string(), 10);
+        let data = manifest.to_vec();
+        let read = DownloadManifest::from_reader(std::io::Cursor::new(data.clone()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(read.app_name_string, "App");
+        assert_eq!(read.files(), manifest.files());
+        assert!(DownloadManifest::from_reader(&data[..20])
+            .unwrap()
+            .is_none());
+        let mut corrupt = data[..41].to_vec();
+        corrupt.extend_from_slice(b"not zlib data");
+        assert!(DownloadManifest::from_reader(corrupt.as_slice())
+            .unwrap()
+            .is_none());
+        assert!(DownloadManifest::from_vec(b"{}".to_vec()).is_none());
+    }
+
+    fn file(filename: &str, file_hash: &str, size: u128) -> FileManifestList {
+        FileManifestList {
+            filename: filename.to_string(),
+            file_hash: file_hash.to_string(),
+            file_chunk_parts: vec![FileChunkPart {
+                guid: "aa".to_string(),
+                link: None,
+                offset: 0,
+                size,
+            }],
+            install_tags: vec![],
+        }
+    }
+
+    #[test]
+    fn file_table() {
+        let mut manifest = manifest(&[("aa", 1)]);
+        manifest.file_manifest_list = vec![
+            file("Content/b.uasset", "bb", 20),
+            file("Config/a.ini", "aa", 10),
+            file("Content/a.uasset", "cc", 30),
+        ];
+        assert_eq!(
+            manifest.file_table(),
+            vec![
+                ("Config/a.ini".to_string(), 10, "aa".to_string()),
+                ("Content/a.uasset".to_string(), 30, "cc".to_string()),
+                ("Content/b.uasset".to_string(), 20, "bb".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn files_json() {
+        let mut manifest = manifest(&[("aa", 1)]);
+        assert_eq!(manifest.files_json(), "[]");
+        manifest.file_manifest_list =
+            vec![file("b.bin", "bb", 20), file("a \"quoted\".ini", "aa", 10)];
+        assert_eq!(
+            manifest.files_json(),
+            r#"[{"filename":"a \"quoted\".ini","size":10,"hash":"aa"},{"filename":"b.bin","size":20,"hash":"bb"}]"#
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::api::types::epic_asset::EpicAsset;
+use crate::api::types::fab_library::FabAsset;
+
+/// Common view over assets from the Epic library and from Fab
+///
+/// Lets generic code like progress tracking or list rendering work with both asset sources.
+pub trait DownloadableAsset {
+    /// Get the asset namespace
+    fn namespace(&self) -> &str;
+
+    /// Get the asset id
+    fn asset_id(&self) -> &str;
+
+    /// Get the human readable name of the asset
+    fn title(&self) -> &str;
+}
+
+impl DownloadableAsset for EpicAsset {
+    fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    fn asset_id(&self) -> &str {
+        &self.asset_id
+    }
+
+    /// Library assets carry no title, the app name is used instead
+    fn title(&self) -> &str {
+        &self.app_name
+    }
+}
+
+impl DownloadableAsset for FabAsset {
+    fn namespace(&self) -> &str {
+        &self.asset_namespace
+    }
+
+    fn asset_id(&self) -> &str {
+        &self.asset_id
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::downloadable::DownloadableAsset;
+    use crate::api::types::epic_asset::EpicAsset;
+    use crate::api::types::fab_library::FabAsset;
+
+    fn describe(asset: &impl DownloadableAsset) -> String {
+        format!(
+            "{}/{}: {}",
+            asset.namespace(),
+            asset.asset_id(),
+            asset.title()
+        )
+    }
+
+    #[test]
+    fn both_sources() {
+        let epic = EpicAsset {
+            app_name: "App".to_string(),
+            namespace: "ns".to_string(),
+            asset_id: "id".to_string(),
+            ..Default::default()
+        };
+        let fab = FabAsset {
+            asset_namespace: "fab-ns".to_string(),
+            asset_id: "fab-id".to_string(),
+            title: "Fab Title".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(describe(&epic), "ns/id: App");
+        assert_eq!(describe(&fab), "fab-ns/fab-id: Fab Title");
+    }
+}
@@ -1,4 +1,6 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[allow(missing_docs)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -23,3 +25,57 @@ pub struct Entitlement {
     pub platform_type: Option<String>,
     pub country: Option<String>,
 }
+
+impl Entitlement {
+    /// Get the grant date, `None` if it can't be parsed
+    pub fn grant_date_parsed(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.grant_date)
+            .ok()
+            .map(|date| date.with_timezone(&Utc))
+    }
+
+    /// Key entitlements by catalog item id
+    ///
+    /// When an item was granted several times, as happens with consumables, the most recent
+    /// grant is kept.
+    pub fn map_by_catalog_item(entitlements: Vec<Entitlement>) -> HashMap<String, Entitlement> {
+        let mut result: HashMap<String, Entitlement> = HashMap::new();
+        for entitlement in entitlements {
+            match result.get(&entitlement.catalog_item_id) {
+                Some(existing)
+                    if existing.grant_date_parsed() >= entitlement.grant_date_parsed() => {}
+                _ => {
+                    result.insert(entitlement.catalog_item_id.clone(), entitlement);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::entitlement::Entitlement;
+
+    fn entitlement(id: &str, catalog_item_id: &str, grant_date: &str) -> Entitlement {
+        Entitlement {
+            id: id.to_string(),
+            catalog_item_id: catalog_item_id.to_string(),
+            grant_date: grant_date.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn map_keeps_most_recent_grant() {
+        let map = Entitlement::map_by_catalog_item(vec![
+            entitlement("1", "item", "2021-01-01T00:00:00.000Z"),
+            entitlement("2", "item", "2023-06-01T12:00:00.000Z"),
+            entitlement("3", "item", "2022-01-01T00:00:00.000Z"),
+            entitlement("4", "other", "2020-01-01T00:00:00.000Z"),
+        ]);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["item"].id, "2");
+        assert_eq!(map["other"].id, "4");
+    }
+}
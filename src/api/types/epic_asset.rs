@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
 
 #[allow(missing_docs)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -12,3 +15,260 @@ pub struct EpicAsset {
     pub asset_id: String,
 }
 
+impl EpicAsset {
+    /// Check whether both assets refer to the same catalog item
+    ///
+    /// Unlike the derived `PartialEq`, which compares every field, this only looks at the
+    /// namespace and catalog item id, so different builds or labels of one item match.
+    pub fn same_item(&self, other: &EpicAsset) -> bool {
+        self.namespace == other.namespace && self.catalog_item_id == other.catalog_item_id
+    }
+
+    /// Get the parsed build version, for comparing builds
+    pub fn build_version_parsed(&self) -> ParsedBuildVersion {
+        ParsedBuildVersion::parse(&self.build_version)
+    }
+
+    /// Get the key identifying the catalog item of this asset
+    pub fn key(&self) -> AssetKey {
+        AssetKey::from(self)
+    }
+}
+
+/// Identity of a catalog item, made of the namespace and catalog item id
+///
+/// Can be used as a `HashMap`/`HashSet` key to deduplicate assets by item regardless of build.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AssetKey {
+    /// Asset namespace
+    pub namespace: String,
+    /// Catalog item id
+    pub catalog_item_id: String,
+}
+
+impl From<&EpicAsset> for AssetKey {
+    fn from(asset: &EpicAsset) -> Self {
+        AssetKey {
+            namespace: asset.namespace.clone(),
+            catalog_item_id: asset.catalog_item_id.clone(),
+        }
+    }
+}
+
+/// Changes between two asset lists, see [`diff_assets`]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct AssetListDiff {
+    /// Assets only in the new list
+    pub added: Vec<EpicAsset>,
+    /// Assets only in the old list
+    pub removed: Vec<EpicAsset>,
+    /// Assets in both lists with a different build version, as `(old, new)`
+    pub updated: Vec<(EpicAsset, EpicAsset)>,
+}
+
+impl AssetListDiff {
+    /// Check whether the lists had no changes
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+/// Compare two asset lists by catalog item, see [`AssetKey`]
+///
+/// An asset counts as updated when its build version changed. Added and updated assets keep
+/// the order of the new list, removed assets the order of the old one.
+pub fn diff_assets(old: &[EpicAsset], new: &[EpicAsset]) -> AssetListDiff {
+    let old_by_key: HashMap<AssetKey, &EpicAsset> =
+        old.iter().map(|asset| (asset.key(), asset)).collect();
+    let new_by_key: HashMap<AssetKey, &EpicAsset> =
+        new.iter().map(|asset| (asset.key(), asset)).collect();
+    let mut diff = AssetListDiff::default();
+    for asset in new {
+        match old_by_key.get(&asset.key()) {
+            None => diff.added.push(asset.clone()),
+            Some(previous) => {
+                if previous.build_version != asset.build_version {
+                    diff.updated.push(((*previous).clone(), asset.clone()));
+                }
+            }
+        }
+    }
+    for asset in old {
+        if !new_by_key.contains_key(&asset.key()) {
+            diff.removed.push(asset.clone());
+        }
+    }
+    diff
+}
+
+/// Parsed build version like `1.2.3-456+++depot`
+///
+/// Consists of the dotted version, the build number after the dash and the suffix after `+++`.
+/// Versions are ordered by the numeric version, then the build number and finally the raw string.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParsedBuildVersion {
+    /// Numeric version components, parsing stops at the first non-numeric component
+    pub version: Vec<u64>,
+    /// Build number
+    pub build: Option<u64>,
+    /// Suffix after `+++`, usually the depot or branch
+    pub suffix: Option<String>,
+    raw: String,
+}
+
+impl ParsedBuildVersion {
+    /// Parse the build version, never fails, unparsable parts are left out
+    pub fn parse(raw: &str) -> ParsedBuildVersion {
+        let (prefix, suffix) = match raw.split_once("+++") {
+            None => (raw, None),
+            Some((prefix, suffix)) => (prefix, Some(suffix.to_string())),
+        };
+        let (version, build) = match prefix.split_once('-') {
+            None => (prefix, None),
+            Some((version, build)) => (version, leading_number(build)),
+        };
+        let mut components = Vec::new();
+        for part in version.split('.') {
+            match leading_number(part) {
+                None => break,
+                Some(n) => components.push(n),
+            }
+        }
+        ParsedBuildVersion {
+            version: components,
+            build,
+            suffix,
+            raw: raw.to_string(),
+        }
+    }
+
+    /// Get the original string
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+}
+
+fn leading_number(s: &str) -> Option<u64> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s[..end].parse().ok()
+}
+
+impl From<&str> for ParsedBuildVersion {
+    fn from(raw: &str) -> Self {
+        ParsedBuildVersion::parse(raw)
+    }
+}
+
+impl fmt::Display for ParsedBuildVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl Ord for ParsedBuildVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.version.len().max(other.version.len());
+        for i in 0..len {
+            let a = self.version.get(i).unwrap_or(&0);
+            let b = other.version.get(i).unwrap_or(&0);
+            match a.cmp(b) {
+                Ordering::Equal => {}
+                ord => return ord,
+            }
+        }
+        self.build
+            .cmp(&other.build)
+            .then_with(|| self.raw.cmp(&other.raw))
+    }
+}
+
+impl PartialOrd for ParsedBuildVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::epic_asset::{diff_assets, AssetKey, EpicAsset, ParsedBuildVersion};
+    use std::collections::HashSet;
+
+    fn asset(catalog_item_id: &str, build_version: &str) -> EpicAsset {
+        EpicAsset {
+            namespace: "ns".to_string(),
+            catalog_item_id: catalog_item_id.to_string(),
+            build_version: build_version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn same_item_ignores_build() {
+        let mut other = asset("a", "1.1");
+        other.label_name = "Live-Windows".to_string();
+        assert!(asset("a", "1.0").same_item(&other));
+        assert_ne!(asset("a", "1.0"), other);
+        assert!(!asset("a", "1.0").same_item(&asset("b", "1.0")));
+        other.namespace = "other".to_string();
+        assert!(!asset("a", "1.0").same_item(&other));
+    }
+
+    #[test]
+    fn asset_key_hashing() {
+        let assets = [asset("a", "1.0"), asset("a", "1.1"), asset("b", "1.0")];
+        let keys: HashSet<AssetKey> = assets.iter().map(EpicAsset::key).collect();
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&AssetKey {
+            namespace: "ns".to_string(),
+            catalog_item_id: "a".to_string(),
+        }));
+        assert_eq!(asset("b", "2.0").key(), AssetKey::from(&asset("b", "1.0")));
+    }
+
+    #[test]
+    fn parse_build_version() {
+        let version = ParsedBuildVersion::parse("4.27.2-18319896+++UE4+Release-4.27");
+        assert_eq!(version.version, vec![4, 27, 2]);
+        assert_eq!(version.build, Some(18319896));
+        assert_eq!(version.suffix, Some("UE4+Release-4.27".to_string()));
+        assert_eq!(version.as_str(), "4.27.2-18319896+++UE4+Release-4.27");
+    }
+
+    #[test]
+    fn parse_unusual_build_version() {
+        let version = ParsedBuildVersion::parse("ue5");
+        assert!(version.version.is_empty());
+        assert_eq!(version.build, None);
+        assert_eq!(ParsedBuildVersion::parse("1.0a.b").version, vec![1, 0]);
+    }
+
+    #[test]
+    fn compare_build_versions() {
+        assert!(ParsedBuildVersion::parse("1.2.10-1") > ParsedBuildVersion::parse("1.2.9-5"));
+        assert!(
+            ParsedBuildVersion::parse("1.2.3-457+++depot")
+                > ParsedBuildVersion::parse("1.2.3-456+++depot")
+        );
+        assert!(ParsedBuildVersion::parse("1.3") > ParsedBuildVersion::parse("1.2.3"));
+        assert!(ParsedBuildVersion::parse("2.0.0") < ParsedBuildVersion::parse("10.0"));
+        let asset = EpicAsset {
+            build_version: "1.0.0-1".to_string(),
+            ..Default::default()
+        };
+        assert!(asset.build_version_parsed() < ParsedBuildVersion::parse("1.0.1-1"));
+    }
+
+    #[test]
+    fn diff_asset_lists() {
+        let old = vec![asset("a", "1.0"), asset("b", "1.0"), asset("c", "1.0")];
+        let new = vec![asset("d", "1.0"), asset("b", "1.1"), asset("c", "1.0")];
+        let diff = diff_assets(&old, &new);
+        assert_eq!(diff.added, vec![asset("d", "1.0")]);
+        assert_eq!(diff.removed, vec![asset("a", "1.0")]);
+        assert_eq!(diff.updated, vec![(asset("b", "1.0"), asset("b", "1.1"))]);
+        assert!(!diff.is_empty());
+        assert!(diff_assets(&old, &old).is_empty());
+        assert_eq!(diff_assets(&[], &old).added.len(), 3);
+        assert_eq!(diff_assets(&old, &[]).removed.len(), 3);
+    }
+}
@@ -37,6 +37,17 @@ impl DownloadInfo {
             .iter()
             .find(|&distribution_point| distribution_point.manifest_url.starts_with(base_url))
     }
+
+    /// Get the manifest hash decoded from hex
+    ///
+    /// Returns `None` unless the hash is a hex encoded SHA-1 or SHA-256, 40 or 64 characters long.
+    pub fn manifest_hash_bytes(&self) -> Option<Vec<u8>> {
+        let hash = self.manifest_hash.as_str();
+        if !(hash.len() == 40 || hash.len() == 64) || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        crate::api::utils::decode_hex(hash).ok()
+    }
 }
 
 /// Distribution Point
@@ -54,3 +65,32 @@ pub struct DistributionPoint {
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Metadata {}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::fab_asset_manifest::DownloadInfo;
+
+    fn info(manifest_hash: &str) -> DownloadInfo {
+        DownloadInfo {
+            manifest_hash: manifest_hash.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn manifest_hash_bytes() {
+        let bytes = info("da39a3ee5e6b4b0d3255bfef95601890afd80709")
+            .manifest_hash_bytes()
+            .unwrap();
+        assert_eq!(bytes.len(), 20);
+        assert_eq!(&bytes[..3], &[0xda, 0x39, 0xa3]);
+        assert_eq!(
+            info(&"AB".repeat(32)).manifest_hash_bytes().unwrap().len(),
+            32
+        );
+        assert!(info("").manifest_hash_bytes().is_none());
+        assert!(info("abc").manifest_hash_bytes().is_none());
+        assert!(info(&"zz".repeat(20)).manifest_hash_bytes().is_none());
+        assert!(info(&"é".repeat(20)).manifest_hash_bytes().is_none());
+    }
+}
@@ -1,6 +1,8 @@
+use crate::api::types::epic_asset::{self, EpicAsset};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use serde_with::DefaultOnNull;
+use std::cmp::Ordering;
 
 /// Fab Library Response
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -9,7 +11,63 @@ pub struct FabLibrary {
     /// Pagination cursors
     pub cursors: Cursor,
     /// Library contents
-    pub results: Vec<Result>,
+    pub results: Vec<FabAsset>,
+}
+
+impl FabLibrary {
+    /// Get the number of assets
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Check whether there are no assets
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Iterate over the assets
+    pub fn iter(&self) -> std::slice::Iter<'_, FabAsset> {
+        self.results.iter()
+    }
+
+    /// Find assets whose title contains the query, ignoring case
+    pub fn find_by_title(&self, query: &str) -> Vec<&FabAsset> {
+        let query = query.to_lowercase();
+        self.results
+            .iter()
+            .filter(|asset| asset.title.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// Find assets in the namespace
+    pub fn find_by_namespace(&self, namespace: &str) -> Vec<&FabAsset> {
+        self.results
+            .iter()
+            .filter(|asset| asset.asset_namespace == namespace)
+            .collect()
+    }
+
+    /// Find assets in the category
+    pub fn by_category(&self, category_id: &str) -> Vec<&FabAsset> {
+        self.results
+            .iter()
+            .filter(|asset| {
+                asset
+                    .categories
+                    .iter()
+                    .any(|category| category.id == category_id)
+            })
+            .collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a FabLibrary {
+    type Item = &'a FabAsset;
+    type IntoIter = std::slice::Iter<'a, FabAsset>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.iter()
+    }
 }
 
 /// Pagination Cursors
@@ -24,7 +82,7 @@ pub struct Cursor {
 #[serde_as]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Result {
+pub struct FabAsset {
     /// Asset ID
     pub asset_id: String,
     /// Asset Namespace
@@ -55,6 +113,146 @@ pub struct Result {
     pub url: String,
 }
 
+impl FabAsset {
+    /// Get the typed distribution method, the raw value stays in `distribution_method`
+    pub fn distribution_method_kind(&self) -> DistributionMethod {
+        DistributionMethod::from(self.distribution_method.as_str())
+    }
+
+    /// Get the project versions that support the engine, see [`ProjectVersion::supports_engine`]
+    pub fn versions_for_engine(&self, engine: &str) -> Vec<&ProjectVersion> {
+        self.project_versions
+            .iter()
+            .filter(|version| version.supports_engine(engine))
+            .collect()
+    }
+
+    /// Get the typed source of the listing, the raw value stays in `source`
+    pub fn source_kind(&self) -> FabSource {
+        FabSource::from(self.source.as_str())
+    }
+
+    /// Check whether the asset can be downloaded with `fab_asset_manifest`
+    ///
+    /// The asset needs a known distribution method and at least one project version with an artifact.
+    pub fn is_downloadable(&self) -> bool {
+        !matches!(
+            self.distribution_method_kind(),
+            DistributionMethod::Other(_)
+        ) && self
+            .project_versions
+            .iter()
+            .any(|version| !version.artifact_id.is_empty())
+    }
+
+    /// Convert to an `EpicAsset` for the given project version
+    ///
+    /// The artifact id becomes the app name and the build version is taken from the Windows
+    /// build if there is one, otherwise from the first build. FAB has no labels or launcher
+    /// catalog item ids, so `label_name` and `catalog_item_id` are left empty.
+    pub fn to_epic_asset(&self, version: &ProjectVersion) -> EpicAsset {
+        let build = version
+            .build_versions
+            .iter()
+            .find(|build| build.platform == "Windows")
+            .or_else(|| version.build_versions.first());
+        EpicAsset {
+            app_name: version.artifact_id.clone(),
+            label_name: String::new(),
+            build_version: build
+                .map(|build| build.build_version.clone())
+                .unwrap_or_default(),
+            catalog_item_id: String::new(),
+            namespace: self.asset_namespace.clone(),
+            asset_id: self.asset_id.clone(),
+        }
+    }
+}
+
+/// Distribution method of a FAB asset
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum DistributionMethod {
+    /// Asset pack, content added to a project
+    AssetPack,
+    /// Complete project
+    CompleteProject,
+    /// Code plugin
+    CodePlugin,
+    /// Unknown distribution method with its raw value
+    Other(String),
+}
+
+impl From<&str> for DistributionMethod {
+    fn from(value: &str) -> Self {
+        match value {
+            "ASSET_PACK" => DistributionMethod::AssetPack,
+            "COMPLETE_PROJECT" => DistributionMethod::CompleteProject,
+            "CODE_PLUGIN" => DistributionMethod::CodePlugin,
+            other => DistributionMethod::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for DistributionMethod {
+    fn from(value: String) -> Self {
+        DistributionMethod::from(value.as_str())
+    }
+}
+
+impl From<DistributionMethod> for String {
+    fn from(value: DistributionMethod) -> Self {
+        match value {
+            DistributionMethod::AssetPack => "ASSET_PACK".to_string(),
+            DistributionMethod::CompleteProject => "COMPLETE_PROJECT".to_string(),
+            DistributionMethod::CodePlugin => "CODE_PLUGIN".to_string(),
+            DistributionMethod::Other(other) => other,
+        }
+    }
+}
+
+/// Source of a FAB listing
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum FabSource {
+    /// Listed on FAB
+    Fab,
+    /// Migrated from the Unreal Engine Marketplace
+    Marketplace,
+    /// Unknown source with its raw value
+    Other(String),
+}
+
+impl From<&str> for FabSource {
+    fn from(value: &str) -> Self {
+        match value {
+            "fab" => FabSource::Fab,
+            "marketplace" => FabSource::Marketplace,
+            other => FabSource::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for FabSource {
+    fn from(value: String) -> Self {
+        FabSource::from(value.as_str())
+    }
+}
+
+impl From<FabSource> for String {
+    fn from(value: FabSource) -> Self {
+        match value {
+            FabSource::Fab => "fab".to_string(),
+            FabSource::Marketplace => "marketplace".to_string(),
+            FabSource::Other(other) => other,
+        }
+    }
+}
+
+/// Library item, previous name of [`FabAsset`]
+#[deprecated(note = "Renamed to FabAsset")]
+pub type Result = FabAsset;
+
 /// Asset Category
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -102,8 +300,41 @@ pub struct ProjectVersion {
     pub target_platforms: Vec<String>,
 }
 
+impl ProjectVersion {
+    /// Check whether the version supports the engine
+    ///
+    /// Matching is loose, "5.3", "UE_5.3" and "ue5.3.2" all match the listed "UE_5.3".
+    pub fn supports_engine(&self, engine: &str) -> bool {
+        let engine = normalize_engine_version(engine);
+        self.engine_versions.iter().any(|version| {
+            let version = normalize_engine_version(version);
+            version.iter().zip(engine.iter()).all(|(a, b)| a == b)
+        })
+    }
+
+    /// Get the newest build for the platform, compared case-insensitively
+    ///
+    /// Builds are ordered by their parsed build version, see [`BuildVersion::parsed`].
+    pub fn latest_build_for_platform(&self, platform: &str) -> Option<&BuildVersion> {
+        self.build_versions
+            .iter()
+            .filter(|build| build.platform.eq_ignore_ascii_case(platform))
+            .max()
+    }
+}
+
+fn normalize_engine_version(version: &str) -> Vec<String> {
+    let version = version.trim().to_lowercase();
+    let version = version
+        .trim_start_matches("ue")
+        .trim_start_matches(['_', ' ', '-']);
+    version.split('.').map(|part| part.to_string()).collect()
+}
+
 /// Build Version
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// Ordered by the parsed build version, then the platform.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BuildVersion {
     /// Build Version
@@ -111,3 +342,216 @@ pub struct BuildVersion {
     /// Platform
     pub platform: String,
 }
+
+impl BuildVersion {
+    /// Get the parsed build version, non-numeric versions parse without failing
+    pub fn parsed(&self) -> epic_asset::ParsedBuildVersion {
+        epic_asset::ParsedBuildVersion::parse(&self.build_version)
+    }
+}
+
+impl Ord for BuildVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.parsed()
+            .cmp(&other.parsed())
+            .then_with(|| self.platform.cmp(&other.platform))
+    }
+}
+
+impl PartialOrd for BuildVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::fab_library::{
+        BuildVersion, Category, DistributionMethod, FabAsset, FabLibrary, FabSource, ProjectVersion,
+    };
+
+    #[test]
+    fn distribution_method_known() {
+        for (raw, method) in [
+            ("ASSET_PACK", DistributionMethod::AssetPack),
+            ("COMPLETE_PROJECT", DistributionMethod::CompleteProject),
+            ("CODE_PLUGIN", DistributionMethod::CodePlugin),
+        ] {
+            let asset = FabAsset {
+                distribution_method: raw.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(asset.distribution_method_kind(), method);
+            assert_eq!(String::from(method), raw);
+        }
+    }
+
+    #[test]
+    fn distribution_method_other() {
+        assert_eq!(
+            DistributionMethod::from("SOMETHING_NEW"),
+            DistributionMethod::Other("SOMETHING_NEW".to_string())
+        );
+        assert_eq!(
+            serde_json::from_str::<DistributionMethod>("\"ASSET_PACK\"").unwrap(),
+            DistributionMethod::AssetPack
+        );
+    }
+
+    #[test]
+    fn fab_source() {
+        assert_eq!(FabSource::from("fab"), FabSource::Fab);
+        assert_eq!(FabSource::from("marketplace"), FabSource::Marketplace);
+        assert_eq!(
+            FabSource::from("other"),
+            FabSource::Other("other".to_string())
+        );
+        assert_eq!(
+            serde_json::to_string(&FabSource::Marketplace).unwrap(),
+            "\"marketplace\""
+        );
+    }
+
+    fn version(artifact_id: &str, engines: &[&str]) -> ProjectVersion {
+        ProjectVersion {
+            artifact_id: artifact_id.to_string(),
+            engine_versions: engines.iter().map(|e| e.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn supports_engine() {
+        let version = version("a", &["UE_5.3", "UE_5.4"]);
+        assert!(version.supports_engine("5.3"));
+        assert!(version.supports_engine("UE_5.4"));
+        assert!(version.supports_engine("ue5.3.2"));
+        assert!(!version.supports_engine("5.5"));
+        assert!(!version.supports_engine("4.27"));
+        assert!(!version.supports_engine("5.31"));
+    }
+
+    #[test]
+    fn versions_for_engine() {
+        let asset = FabAsset {
+            project_versions: vec![
+                version("a", &["UE_4.27"]),
+                version("b", &["UE_5.3", "UE_5.4"]),
+                version("c", &["UE_5.4"]),
+            ],
+            ..Default::default()
+        };
+        let versions: Vec<&str> = asset
+            .versions_for_engine("5.4")
+            .iter()
+            .map(|v| v.artifact_id.as_str())
+            .collect();
+        assert_eq!(versions, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn is_downloadable() {
+        let mut asset = FabAsset {
+            distribution_method: "ASSET_PACK".to_string(),
+            ..Default::default()
+        };
+        assert!(!asset.is_downloadable());
+        asset.project_versions.push(version("a", &["UE_5.4"]));
+        assert!(asset.is_downloadable());
+        asset.distribution_method = "EXTERNAL".to_string();
+        assert!(!asset.is_downloadable());
+    }
+
+    #[test]
+    fn library_search() {
+        let asset = |title: &str, namespace: &str, category: &str| FabAsset {
+            title: title.to_string(),
+            asset_namespace: namespace.to_string(),
+            categories: vec![Category {
+                id: category.to_string(),
+                name: None,
+            }],
+            ..Default::default()
+        };
+        let library = FabLibrary {
+            results: vec![
+                asset("Medieval Village", "ns1", "environments"),
+                asset("Sci-Fi Corridor", "ns2", "environments"),
+                asset("Easy Multi Save", "ns1", "code-plugins"),
+            ],
+            ..Default::default()
+        };
+        let titles = |assets: Vec<&FabAsset>| -> Vec<String> {
+            assets.iter().map(|a| a.title.clone()).collect()
+        };
+        assert_eq!(
+            titles(library.find_by_title("VILLAGE")),
+            vec!["Medieval Village"]
+        );
+        assert!(library.find_by_title("castle").is_empty());
+        assert_eq!(
+            titles(library.find_by_namespace("ns1")),
+            vec!["Medieval Village", "Easy Multi Save"]
+        );
+        assert_eq!(
+            titles(library.by_category("environments")),
+            vec!["Medieval Village", "Sci-Fi Corridor"]
+        );
+    }
+
+    #[test]
+    fn to_epic_asset() {
+        let asset = FabAsset {
+            asset_id: "asset".to_string(),
+            asset_namespace: "ns".to_string(),
+            ..Default::default()
+        };
+        let version = ProjectVersion {
+            artifact_id: "Artifact".to_string(),
+            build_versions: vec![
+                BuildVersion {
+                    build_version: "1.0-Mac".to_string(),
+                    platform: "Mac".to_string(),
+                },
+                BuildVersion {
+                    build_version: "1.0-Windows".to_string(),
+                    platform: "Windows".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        let epic = asset.to_epic_asset(&version);
+        assert_eq!(epic.app_name, "Artifact");
+        assert_eq!(epic.namespace, "ns");
+        assert_eq!(epic.asset_id, "asset");
+        assert_eq!(epic.build_version, "1.0-Windows");
+        assert!(epic.catalog_item_id.is_empty());
+    }
+
+    #[test]
+    fn latest_build_for_platform() {
+        let build = |build_version: &str, platform: &str| BuildVersion {
+            build_version: build_version.to_string(),
+            platform: platform.to_string(),
+        };
+        let version = ProjectVersion {
+            build_versions: vec![
+                build("1.2.0-10", "Windows"),
+                build("1.10.0-3", "Windows"),
+                build("2.0.0-1", "Mac"),
+                build("1.9.0-20", "windows"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            version.latest_build_for_platform("Windows"),
+            Some(&build("1.10.0-3", "Windows"))
+        );
+        assert_eq!(
+            version.latest_build_for_platform("mac"),
+            Some(&build("2.0.0-1", "Mac"))
+        );
+        assert!(version.latest_build_for_platform("Linux").is_none());
+        assert!(build("beta", "Windows") < build("0.1", "Windows"));
+    }
+}
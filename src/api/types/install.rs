@@ -0,0 +1,66 @@
+/// Progress of an asset installation, reported after every finished file
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct InstallProgress {
+    /// Name of the file that was just finished
+    pub filename: String,
+    /// How the file was finished
+    pub state: FileInstallState,
+    /// Number of finished files
+    pub files_completed: usize,
+    /// Number of files in the installation
+    pub files_total: usize,
+    /// Size of the finished files
+    pub bytes_completed: u128,
+    /// Size of all files in the installation
+    pub bytes_total: u128,
+}
+
+/// How a file of an installation was finished
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileInstallState {
+    /// The file was downloaded
+    #[default]
+    Downloaded,
+    /// The file was already present with the expected hash and was skipped
+    AlreadyPresent,
+}
+
+/// Result of verifying an installed file against the manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileStatus {
+    /// The file is present with the expected hash
+    Ok(String),
+    /// The file does not exist
+    Missing(String),
+    /// The file has the expected size but a different hash
+    Corrupt(String),
+    /// The file exists but could not be read, e.g. for lack of permissions
+    Unreadable(String),
+    /// The file has a different size than the manifest expects
+    SizeMismatch {
+        /// Name of the file
+        filename: String,
+        /// Size according to the manifest
+        expected: u128,
+        /// Size on disk
+        actual: u128,
+    },
+}
+
+impl FileStatus {
+    /// Get the name of the verified file
+    pub fn filename(&self) -> &str {
+        match self {
+            FileStatus::Ok(filename)
+            | FileStatus::Missing(filename)
+            | FileStatus::Corrupt(filename)
+            | FileStatus::Unreadable(filename) => filename,
+            FileStatus::SizeMismatch { filename, .. } => filename,
+        }
+    }
+
+    /// Check whether the file is intact
+    pub fn is_ok(&self) -> bool {
+        matches!(self, FileStatus::Ok(_))
+    }
+}
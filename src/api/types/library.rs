@@ -8,6 +8,32 @@ pub struct Library {
     pub response_metadata: Option<ResponseMetadata>,
 }
 
+impl Library {
+    /// Get the number of records
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Check whether there are no records
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Iterate over the records
+    pub fn iter(&self) -> std::slice::Iter<'_, Record> {
+        self.records.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Library {
+    type Item = &'a Record;
+    type IntoIter = std::slice::Iter<'a, Record>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.iter()
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
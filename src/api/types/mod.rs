@@ -30,3 +30,18 @@ pub mod fab_library;
 
 /// Fab Asset Manifest
 pub mod fab_asset_manifest;
+
+/// Request description structures
+pub mod request;
+
+/// Installation progress structures
+pub mod install;
+
+/// Catalog offer structures
+pub mod offer;
+
+/// Service status structures
+pub mod service_status;
+
+/// Common asset trait over the asset sources
+pub mod downloadable;
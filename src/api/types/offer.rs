@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[allow(missing_docs)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Offer {
+    pub id: String,
+    pub namespace: String,
+    pub title: Option<String>,
+    pub currency_code: Option<String>,
+    /// Price before discounts, in the smallest currency unit
+    #[serde(alias = "originalPrice")]
+    pub base_price: Option<i64>,
+    /// Current price with discounts applied, in the smallest currency unit
+    #[serde(alias = "currentPrice")]
+    pub discount_price: Option<i64>,
+    /// End of the current sale
+    pub sale_end_date: Option<DateTime<Utc>>,
+}
+
+impl Offer {
+    /// Check whether the offer is currently discounted
+    pub fn is_discounted(&self) -> bool {
+        match (self.base_price, self.discount_price) {
+            (Some(base), Some(discount)) => discount < base,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::offer::Offer;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn deserialize_offer() {
+        let offer: Offer = serde_json::from_str(
+            r#"{
+                "id": "0c40923dd1174a768f732a3b013dcff2",
+                "namespace": "ue",
+                "title": "City Sample",
+                "description": "City Sample",
+                "offerType": "BASE_GAME",
+                "currencyCode": "USD",
+                "basePrice": 2999,
+                "discountPrice": 1499,
+                "saleEndDate": "2025-01-09T16:00:00.000Z",
+                "effectiveDate": "2024-06-01T00:00:00.000Z",
+                "keyImages": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(offer.title, Some("City Sample".to_string()));
+        assert_eq!(offer.currency_code, Some("USD".to_string()));
+        assert_eq!(offer.base_price, Some(2999));
+        assert_eq!(offer.discount_price, Some(1499));
+        assert_eq!(
+            offer.sale_end_date,
+            Some(Utc.with_ymd_and_hms(2025, 1, 9, 16, 0, 0).unwrap())
+        );
+        assert!(offer.is_discounted());
+
+        let aliased: Offer = serde_json::from_str(
+            r#"{"id":"o","namespace":"ue","currencyCode":"EUR","originalPrice":1999,"currentPrice":1999}"#,
+        )
+        .unwrap();
+        assert_eq!(aliased.base_price, Some(1999));
+        assert_eq!(aliased.discount_price, Some(1999));
+        assert_eq!(aliased.sale_end_date, None);
+        assert!(!aliased.is_discounted());
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use url::Url;
+
+/// API operation that can be described without sending it
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestOperation {
+    /// List launcher assets
+    Assets {
+        /// Platform, defaults to Windows
+        platform: Option<String>,
+        /// Label, defaults to Live
+        label: Option<String>,
+    },
+    /// Get a page of the user library
+    LibraryItems {
+        /// Include item metadata
+        include_metadata: bool,
+        /// Cursor of the page, None for the first one
+        cursor: Option<String>,
+    },
+    /// Get the FAB asset manifest
+    FabAssetManifest {
+        /// Artifact ID
+        artifact_id: String,
+        /// Asset namespace
+        namespace: String,
+        /// Asset ID
+        asset_id: String,
+        /// Platform, defaults to Windows
+        platform: Option<String>,
+    },
+    /// Get a page of the FAB library
+    FabLibrary {
+        /// Account ID
+        account_id: String,
+        /// Cursor of the page, None for the first one
+        cursor: Option<String>,
+    },
+}
+
+/// Description of a prepared request that was not sent
+///
+/// The headers include the Authorization header with the current access token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestDescription {
+    /// HTTP method
+    pub method: String,
+    /// Full URL including the query
+    pub url: Url,
+    /// Request headers, including the client defaults
+    pub headers: HashMap<String, String>,
+    /// Request body, if any
+    pub body: Option<String>,
+}
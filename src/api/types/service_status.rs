@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+#[allow(missing_docs)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceStatus {
+    pub service_instance_id: String,
+    pub status: String,
+    pub message: Option<String>,
+    pub maintenance_uri: Option<String>,
+    #[serde(default)]
+    pub allowed_actions: Vec<String>,
+    #[serde(default)]
+    pub banned: bool,
+}
+
+impl ServiceStatus {
+    /// Check whether the service is up
+    pub fn is_up(&self) -> bool {
+        self.status.eq_ignore_ascii_case("UP")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::types::service_status::ServiceStatus;
+
+    #[test]
+    fn deserialize_lightswitch_response() {
+        let status: ServiceStatus = serde_json::from_str(
+            r#"{
+                "serviceInstanceId": "launcher",
+                "status": "UP",
+                "message": "Launcher is up",
+                "maintenanceUri": null,
+                "overrideCatalogIds": ["a7f138b2e51945ffbfdacc1af0541053"],
+                "allowedActions": ["PLAY", "DOWNLOAD"],
+                "banned": false,
+                "launcherInfoDTO": {
+                    "appName": "Launcher",
+                    "catalogItemId": "4fe75bbc5a674f4f9b356b5c90567da5",
+                    "namespace": "epic"
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(status.service_instance_id, "launcher");
+        assert!(status.is_up());
+        assert_eq!(status.message, Some("Launcher is up".to_string()));
+        assert_eq!(status.maintenance_uri, None);
+        assert_eq!(status.allowed_actions, vec!["PLAY", "DOWNLOAD"]);
+        assert!(!status.banned);
+
+        let down: ServiceStatus = serde_json::from_str(
+            r#"{"serviceInstanceId":"launcher","status":"DOWN","message":"Maintenance","maintenanceUri":"https://status.epicgames.com/"}"#,
+        )
+        .unwrap();
+        assert!(!down.is_up());
+        assert!(down.allowed_actions.is_empty());
+        assert_eq!(
+            down.maintenance_uri,
+            Some("https://status.epicgames.com/".to_string())
+        );
+    }
+}
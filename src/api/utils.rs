@@ -90,6 +90,53 @@ pub(crate) fn decode_hex(s: &str) -> Result<Vec<u8>, ParseIntError> {
         .collect()
 }
 
+/// Format a size in bytes with binary units, e.g. "4.21 GiB"
+pub fn human_bytes(n: u128) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut size = n as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", n, units[unit])
+    } else {
+        format!("{:.2} {}", size, units[unit])
+    }
+}
+
+const ROLLING_HASH_POLY: u64 = 0xC96C5795D7870F42;
+
+const ROLLING_HASH_TABLE: [u64; 256] = rolling_hash_table();
+
+const fn rolling_hash_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut value = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 {
+                (value >> 1) ^ ROLLING_HASH_POLY
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+}
+
+/// Compute Epic's rolling hash (FRollingHash) of the chunk data, as stored in `Chunk::hash`
+pub fn chunk_rolling_hash(data: &[u8]) -> u64 {
+    data.iter().fold(0u64, |hash, byte| {
+        hash.rotate_left(1) ^ ROLLING_HASH_TABLE[*byte as usize]
+    })
+}
+
 pub(crate) fn write_fstring(string: String) -> Vec<u8> {
     let mut meta: Vec<u8> = Vec::new();
     if !string.is_empty() {
@@ -110,8 +157,8 @@ pub(crate) fn write_fstring(string: String) -> Vec<u8> {
 #[cfg(test)]
 mod tests {
     use crate::api::utils::{
-        bigblob_to_num, blob_to_num, do_vecs_match, read_fstring, read_le, read_le_64,
-        read_le_64_signed, read_le_signed,
+        bigblob_to_num, blob_to_num, chunk_rolling_hash, do_vecs_match, human_bytes, read_fstring,
+        read_le, read_le_64, read_le_64_signed, read_le_signed,
     };
     use num::bigint::ToBigUint;
 
@@ -119,14 +166,14 @@ mod tests {
     fn vector_match() {
         let a = vec![0, 0, 0];
         let b = vec![0, 0, 0];
-        assert_eq!(do_vecs_match(&a, &b), true);
+        assert!(do_vecs_match(&a, &b));
     }
 
     #[test]
     fn vector_not_match() {
         let a = vec![0, 0, 0];
         let b = vec![0, 0, 1];
-        assert_eq!(do_vecs_match(&a, &b), false);
+        assert!(!do_vecs_match(&a, &b));
     }
 
     #[test]
@@ -195,4 +242,23 @@ mod tests {
         );
         assert_eq!(position, 14)
     }
+
+    #[test]
+    fn human_bytes_boundaries() {
+        assert_eq!(human_bytes(0), "0 B");
+        assert_eq!(human_bytes(1023), "1023 B");
+        assert_eq!(human_bytes(1024), "1.00 KiB");
+        assert_eq!(human_bytes(1536), "1.50 KiB");
+        assert_eq!(human_bytes(4_520_000_000), "4.21 GiB");
+    }
+
+    #[test]
+    fn rolling_hash() {
+        assert_eq!(chunk_rolling_hash(b""), 0);
+        assert_eq!(chunk_rolling_hash(b"abc"), 0xbc9d3899098133a7);
+        assert_eq!(
+            chunk_rolling_hash(b"The quick brown fox jumps over the lazy dog"),
+            0x562894a34481e908
+        );
+    }
 }
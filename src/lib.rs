@@ -17,28 +17,122 @@
 //!  - Get Entitlements
 //!  - Get Library Items
 //!  - Generate download links for chunks
+//!  - Download and install assets
+//!
+//! ## Features
+//!  - `rustls-tls` (default) - use rustls for TLS, no system OpenSSL needed
+//!  - `native-tls` - use the platform TLS library instead, disable default features to drop rustls
 
+use crate::api::fab::FabLibraryOptions;
 use crate::api::types::account::{AccountData, AccountInfo, UserData};
+use crate::api::types::chunk::Chunk;
+use crate::api::types::download_manifest::FileManifestList;
+use crate::api::types::downloadable::DownloadableAsset;
 use crate::api::types::epic_asset::EpicAsset;
-use crate::api::types::fab_asset_manifest::DownloadInfo;
+use crate::api::types::fab_asset_manifest::{DistributionPoint, DownloadInfo};
+use crate::api::types::fab_library::FabAsset;
 use crate::api::types::friends::Friend;
-use crate::api::{EpicAPI};
+use crate::api::types::install::{FileStatus, InstallProgress};
+use crate::api::types::offer::Offer;
+use crate::api::types::request::{RequestDescription, RequestOperation};
+use crate::api::types::service_status::ServiceStatus;
+use crate::api::{ClientConfig, EpicAPI};
 
 use api::types::asset_info::{AssetInfo, GameToken};
 use api::types::asset_manifest::AssetManifest;
 use api::types::download_manifest::DownloadManifest;
 use api::types::entitlement::Entitlement;
-use api::types::library::Library;
+use api::types::library::{Library, Record};
+use futures::Stream;
 use log::{error, info, warn};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 use crate::api::error::EpicAPIError;
 
 /// Module for authenticated API communication
 pub mod api;
 
 /// Struct to manage the communication with the Epic Games Store Api
+///
+/// Clones share the session: logging in, a token refresh or a logout through any clone
+/// applies to all of them, as do the cookies and the cached account details.
 #[derive(Default, Debug, Clone)]
 pub struct EpicGames {
     egs: EpicAPI,
+    account_cache: Arc<Mutex<Option<AccountData>>>,
+}
+
+/// Builder for `EpicGames` with custom HTTP client settings
+///
+/// Options left unset keep the reqwest defaults.
+#[derive(Default, Debug, Clone)]
+pub struct EpicGamesBuilder {
+    config: ClientConfig,
+}
+
+impl EpicGamesBuilder {
+    /// Maximum number of idle connections kept open per host
+    ///
+    /// Chunks are all fetched from a single CDN host, so for concurrent installs a value
+    /// matching the chunk concurrency, e.g. 16-32, lets every download reuse a connection.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.config.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// How long an idle connection is kept open, reqwest defaults to 90 seconds
+    ///
+    /// Between install batches 30-90 seconds keeps the CDN connections warm.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.config.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Choose between HTTP/2 multiplexing and HTTP/1.1 with many connections
+    ///
+    /// `true` keeps negotiating HTTP/2 with the server and tunes its flow control windows for
+    /// large downloads, `false` restricts every client, including the chunk downloads, to HTTP/1.1.
+    pub fn prefer_http2(mut self, prefer: bool) -> Self {
+        self.config.prefer_http2 = Some(prefer);
+        self
+    }
+
+    /// Number of attempts for GET requests failing with a network error, defaults to 3
+    ///
+    /// Only connection level failures like resets or timeouts are retried, never error statuses.
+    pub fn retry_attempts(mut self, attempts: u32) -> Self {
+        self.config.retry_attempts = Some(attempts);
+        self
+    }
+
+    /// Timeout for every request, from connecting until the response body is read
+    ///
+    /// Unset by default, so requests can wait indefinitely.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Default timeout for manifest and chunk downloads
+    ///
+    /// Replaces the `timeout` for these requests, so it can be set longer than the timeout
+    /// used by the quick metadata calls. A timeout passed to a single download call, e.g.
+    /// `download_chunk_with_timeout`, takes precedence.
+    pub fn download_timeout(mut self, timeout: Duration) -> Self {
+        self.config.download_timeout = Some(timeout);
+        self
+    }
+
+    /// Create the `EpicGames` object
+    pub fn build(self) -> EpicGames {
+        EpicGames {
+            egs: EpicAPI::with_config(self.config),
+            account_cache: Default::default(),
+        }
+    }
 }
 
 impl EpicGames {
@@ -46,12 +140,18 @@ impl EpicGames {
     pub fn new() -> Self {
         EpicGames {
             egs: EpicAPI::new(),
+            account_cache: Default::default(),
         }
     }
 
+    /// Create a builder to configure the HTTP client before creating the object
+    pub fn builder() -> EpicGamesBuilder {
+        EpicGamesBuilder::default()
+    }
+
     /// Check whether the user is logged in
     pub fn is_logged_in(&self) -> bool {
-        if let Some(exp) = self.egs.user_data.expires_at {
+        if let Some(exp) = self.egs.user_data().expires_at {
             let now = chrono::offset::Utc::now();
             let td = exp - now;
             if td.num_seconds() > 600 {
@@ -63,12 +163,49 @@ impl EpicGames {
 
     /// Get User details
     pub fn user_details(&self) -> UserData {
-        self.egs.user_data.clone()
+        self.egs.user_data()
+    }
+
+    /// Get the account id of the logged in user, without a network call
+    pub fn account_id(&self) -> Option<String> {
+        self.egs.user_data().account_id
+    }
+
+    /// Get the display name of the logged in user, without a network call
+    ///
+    /// Returns `None` until a login has completed.
+    pub fn display_name(&self) -> Option<String> {
+        self.egs.user_data().display_name
     }
 
     /// Update User Details
+    ///
+    /// Drops the cached account details, as the details may belong to another session.
     pub fn set_user_details(&mut self, user_details: UserData) {
-        self.egs.user_data.update(user_details);
+        self.invalidate_account_cache();
+        self.egs.update_user_data(user_details);
+    }
+
+    /// Describe the request an operation would send, without sending it
+    ///
+    /// Useful for comparing the crate's requests with the official launcher's traffic.
+    /// The returned headers contain the current access token.
+    pub fn describe_request(&self, operation: RequestOperation) -> Option<RequestDescription> {
+        self.egs.describe_request(operation)
+    }
+
+    /// Export the session cookies, including the FAB session and CSRF cookies
+    ///
+    /// Store the data and pass it to `import_cookies` after a restart to keep the sessions.
+    pub fn export_cookies(&self) -> Vec<u8> {
+        self.egs.export_cookies()
+    }
+
+    /// Import session cookies previously exported with `export_cookies`
+    ///
+    /// Replaces all current cookies, expired cookies are skipped.
+    pub fn import_cookies(&mut self, data: &[u8]) -> Result<(), EpicAPIError> {
+        self.egs.import_cookies(data)
     }
 
     /// Start session with auth code
@@ -77,20 +214,30 @@ impl EpicGames {
         exchange_token: Option<String>,
         authorization_code: Option<String>,
     ) -> bool {
+        self.invalidate_account_cache();
         self.egs
             .start_session(exchange_token, authorization_code)
             .await
             .unwrap_or(false)
     }
 
+    /// Refresh the access token using the stored refresh token
+    ///
+    /// Fails with `EpicAPIError::InvalidCredentials` when there is no refresh token.
+    pub async fn refresh_token(&mut self) -> Result<(), EpicAPIError> {
+        self.egs.refresh_token().await
+    }
+
     /// Invalidate existing session
     pub async fn logout(&mut self) -> bool {
+        self.invalidate_account_cache();
         self.egs.invalidate_sesion().await
     }
 
     /// Perform login based on previous authentication
     pub async fn login(&mut self) -> bool {
-        if let Some(exp) = self.egs.user_data.expires_at {
+        self.invalidate_account_cache();
+        if let Some(exp) = self.egs.user_data().expires_at {
             let now = chrono::offset::Utc::now();
             let td = exp - now;
             if td.num_seconds() > 600 {
@@ -110,7 +257,7 @@ impl EpicGames {
             }
         }
         info!("Logging in...");
-        if let Some(exp) = self.egs.user_data.refresh_expires_at {
+        if let Some(exp) = self.egs.user_data().refresh_expires_at {
             let now = chrono::offset::Utc::now();
             let td = exp - now;
             if td.num_seconds() > 600 {
@@ -137,12 +284,44 @@ impl EpicGames {
         platform: Option<String>,
         label: Option<String>,
     ) -> Vec<EpicAsset> {
-        self.egs
-            .assets(platform, label)
+        self.try_list_assets(platform, label)
             .await
             .unwrap_or_else(|_| Vec::new())
     }
 
+    /// Returns all assets, propagating the error on failure
+    pub async fn try_list_assets(
+        &mut self,
+        platform: Option<String>,
+        label: Option<String>,
+    ) -> Result<Vec<EpicAsset>, EpicAPIError> {
+        self.egs.assets(platform, label).await
+    }
+
+    /// Returns all assets if they changed since the last call, for cheap polling
+    ///
+    /// The `ETag` and `Last-Modified` of the previous response are sent back, `Ok(None)` means
+    /// the list is unchanged. The first call for a platform and label always returns the list.
+    pub async fn list_assets_if_modified(
+        &mut self,
+        platform: Option<String>,
+        label: Option<String>,
+    ) -> Result<Option<Vec<EpicAsset>>, EpicAPIError> {
+        self.egs.assets_if_modified(platform, label).await
+    }
+
+    /// Returns assets of all the labels, fetched concurrently
+    ///
+    /// Assets present in several labels with the same build are only returned once.
+    /// Labels that failed to fetch are left out.
+    pub async fn list_assets_all_labels(
+        &mut self,
+        platform: Option<String>,
+        labels: Vec<String>,
+    ) -> Vec<EpicAsset> {
+        self.egs.assets_all_labels(platform, labels).await
+    }
+
     /// Return asset
     pub async fn asset_manifest(
         &mut self,
@@ -152,14 +331,31 @@ impl EpicGames {
         item_id: Option<String>,
         app: Option<String>,
     ) -> Option<AssetManifest> {
-        match self
-            .egs
+        self.egs
             .asset_manifest(platform, label, namespace, item_id, app)
             .await
-        {
-            Ok(a) => Some(a),
-            Err(_) => None,
+            .ok()
+    }
+
+    /// Return the asset manifest of a specific build version
+    ///
+    /// The manifest is fetched for the default label and only the elements of the requested
+    /// build are kept. Returns `None` when the endpoint does not offer that build.
+    pub async fn asset_manifest_by_build(
+        &mut self,
+        namespace: String,
+        item_id: String,
+        app: String,
+        build_version: &str,
+    ) -> Option<AssetManifest> {
+        let manifest = self
+            .asset_manifest(None, None, Some(namespace), Some(item_id), Some(app))
+            .await?;
+        let result = manifest.for_build(build_version);
+        if result.is_none() {
+            warn!("Build {} is not available", build_version);
         }
+        result
     }
 
     /// Return Fab Asset Manifest
@@ -180,44 +376,235 @@ impl EpicGames {
         }
     }
 
+    /// Return Fab Asset Manifests for several platforms, keyed by platform
+    ///
+    /// The platforms are queried concurrently, platforms that failed are left out.
+    pub async fn fab_asset_manifest_all_platforms(
+        &self,
+        artifact_id: &str,
+        namespace: &str,
+        asset_id: &str,
+        platforms: &[&str],
+    ) -> HashMap<String, Vec<DownloadInfo>> {
+        self.egs
+            .fab_asset_manifest_all_platforms(artifact_id, namespace, asset_id, platforms)
+            .await
+    }
+
+    /// Return Fab Asset Manifest for an asset from any source
+    pub async fn fab_asset_manifest_for(
+        &self,
+        asset: &impl DownloadableAsset,
+        artifact_id: &str,
+        platform: Option<&str>,
+    ) -> Result<Vec<DownloadInfo>, EpicAPIError> {
+        self.egs
+            .fab_asset_manifest(artifact_id, asset.namespace(), asset.asset_id(), platform)
+            .await
+    }
+
+    /// Return the Download Manifests for an asset from any source
+    ///
+    /// Resolves the Fab Asset Manifest and fetches a Download Manifest for each download,
+    /// downloads whose manifest could not be fetched are left out.
+    pub async fn fab_download_manifests_for(
+        &self,
+        asset: &impl DownloadableAsset,
+        artifact_id: &str,
+        platform: Option<&str>,
+    ) -> Result<Vec<DownloadManifest>, EpicAPIError> {
+        let infos = self
+            .fab_asset_manifest_for(asset, artifact_id, platform)
+            .await?;
+        let mut manifests = Vec::new();
+        for info in infos {
+            match self.egs.fab_download_manifest_auto(info).await {
+                Ok((manifest, _)) => manifests.push(manifest),
+                Err(e) => warn!(
+                    "Failed to get download manifest for {}: {:?}",
+                    asset.title(),
+                    e
+                ),
+            }
+        }
+        Ok(manifests)
+    }
+
+    /// Return Fab Asset Manifest, retrying while the endpoint throttles
+    ///
+    /// Gives up with `EpicAPIError::FabTimeout` after `max_attempts` throttled attempts.
+    /// Requests that are permanently forbidden fail right away with `EpicAPIError::APIError`.
+    pub async fn fab_asset_manifest_with_attempts(
+        &self,
+        artifact_id: &str,
+        namespace: &str,
+        asset_id: &str,
+        platform: Option<&str>,
+        max_attempts: u32,
+    ) -> Result<Vec<DownloadInfo>, EpicAPIError> {
+        self.egs
+            .fab_asset_manifest_with_attempts(
+                artifact_id,
+                namespace,
+                asset_id,
+                platform,
+                max_attempts,
+            )
+            .await
+    }
+
     /// Returns info for an asset
     pub async fn asset_info(&mut self, asset: EpicAsset) -> Option<AssetInfo> {
-        match self.egs.asset_info(asset.clone()).await {
-            Ok(mut a) => a.remove(asset.catalog_item_id.as_str()),
+        self.try_asset_info(asset).await.unwrap_or(None)
+    }
+
+    /// Returns info for an asset identified only by its namespace and catalog item id
+    pub async fn asset_info_by_id(
+        &mut self,
+        namespace: &str,
+        catalog_item_id: &str,
+    ) -> Option<AssetInfo> {
+        self.asset_info(EpicAsset {
+            namespace: namespace.to_string(),
+            catalog_item_id: catalog_item_id.to_string(),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Returns info for an asset, propagating the error on failure
+    ///
+    /// `Ok(None)` means the request succeeded but the catalog did not contain the asset.
+    pub async fn try_asset_info(
+        &mut self,
+        asset: EpicAsset,
+    ) -> Result<Option<AssetInfo>, EpicAPIError> {
+        let mut info = self.egs.asset_info(asset.clone()).await?;
+        Ok(info.remove(asset.catalog_item_id.as_str()))
+    }
+
+    /// Returns info for an asset, optionally without the DLC and main game details
+    ///
+    /// Leaving the details out makes the request lighter when only the asset itself is needed.
+    /// Titles and descriptions are localized for the `locale` (e.g. "en"), metadata for the `country` (e.g. "us").
+    pub async fn asset_info_opts(
+        &mut self,
+        asset: EpicAsset,
+        include_dlc: bool,
+        include_main_game: bool,
+        country: &str,
+        locale: &str,
+    ) -> Option<AssetInfo> {
+        match self
+            .egs
+            .asset_info_opts(
+                asset.clone(),
+                include_dlc,
+                include_main_game,
+                country,
+                locale,
+            )
+            .await
+        {
+            Ok(mut info) => info.remove(asset.catalog_item_id.as_str()),
             Err(_) => None,
         }
     }
 
+    /// Returns info for many catalog items of one namespace, keyed by catalog item id
+    ///
+    /// The ids are sent in bulk requests of up to 50 ids instead of one request per item.
+    /// Items of failed requests are left out, the others are still returned.
+    pub async fn catalog_items(
+        &mut self,
+        namespace: &str,
+        ids: &[String],
+    ) -> HashMap<String, AssetInfo> {
+        self.egs.catalog_items(namespace, ids).await
+    }
+
+    /// Returns the pricing of a catalog offer for the country
+    pub async fn asset_offers(
+        &mut self,
+        namespace: &str,
+        offer_id: &str,
+        country: &str,
+    ) -> Option<Offer> {
+        self.egs.offer(namespace, offer_id, country).await.ok()
+    }
+
+    /// Returns the status of the launcher services
+    ///
+    /// Lets tools report Epic maintenance instead of failing with opaque errors.
+    pub async fn service_status(&mut self) -> Option<ServiceStatus> {
+        self.egs.service_status().await.ok()
+    }
+
+    /// Returns info for many assets, keyed by catalog item id
+    ///
+    /// Assets sharing a namespace are fetched with bulk requests, at most `concurrency` of which
+    /// run at the same time. Assets that failed to fetch are missing from the result.
+    pub async fn assets_info_concurrent(
+        &self,
+        assets: Vec<EpicAsset>,
+        concurrency: usize,
+    ) -> HashMap<String, AssetInfo> {
+        self.egs.assets_info_concurrent(assets, concurrency).await
+    }
+
+    /// Returns catalog info for a FAB asset
+    ///
+    /// FAB library entries may lack images or other metadata, the catalog has the same data
+    /// the launcher assets use, looked up by the FAB asset namespace and id.
+    pub async fn fab_asset_details(
+        &mut self,
+        namespace: &str,
+        asset_id: &str,
+    ) -> Option<AssetInfo> {
+        self.asset_info_by_id(namespace, asset_id).await
+    }
+
     /// Returns account details
     pub async fn account_details(&mut self) -> Option<AccountData> {
-        match self.egs.account_details().await {
-            Ok(a) => Some(a),
-            Err(_) => None,
+        self.try_account_details().await.ok()
+    }
+
+    /// Returns account details, propagating the error on failure
+    pub async fn try_account_details(&mut self) -> Result<AccountData, EpicAPIError> {
+        self.egs.account_details().await
+    }
+
+    /// Returns account details, fetching them only on the first call
+    ///
+    /// The result is kept until `invalidate_account_cache` is called or the session changes
+    /// through `auth_code`, `login`, `set_user_details` or `logout`.
+    pub async fn account_details_cached(&mut self) -> Option<AccountData> {
+        if let Some(account) = self.account_cache.lock().unwrap().clone() {
+            return Some(account);
         }
+        let account = self.account_details().await;
+        *self.account_cache.lock().unwrap() = account.clone();
+        account
+    }
+
+    /// Drop the account details cached by `account_details_cached`
+    pub fn invalidate_account_cache(&mut self) {
+        *self.account_cache.lock().unwrap() = None;
     }
 
     /// Returns account id info
     pub async fn account_ids_details(&mut self, ids: Vec<String>) -> Option<Vec<AccountInfo>> {
-        match self.egs.account_ids_details(ids).await {
-            Ok(a) => Some(a),
-            Err(_) => None,
-        }
+        self.egs.account_ids_details(ids).await.ok()
     }
 
     /// Returns account id info
     pub async fn account_friends(&mut self, include_pending: bool) -> Option<Vec<Friend>> {
-        match self.egs.account_friends(include_pending).await {
-            Ok(a) => Some(a),
-            Err(_) => None,
-        }
+        self.egs.account_friends(include_pending).await.ok()
     }
 
     /// Returns game token
     pub async fn game_token(&mut self) -> Option<GameToken> {
-        match self.egs.game_token().await {
-            Ok(a) => Some(a),
-            Err(_) => None,
-        }
+        self.egs.game_token().await.ok()
     }
 
     /// Returns ownership token for an Asset
@@ -228,36 +615,202 @@ impl EpicGames {
         }
     }
 
+    /// Returns ownership tokens for multiple assets, keyed by catalog item id
+    ///
+    /// Assets whose token could not be fetched are missing from the result.
+    pub async fn ownership_tokens(&mut self, assets: Vec<EpicAsset>) -> HashMap<String, String> {
+        self.egs.ownership_tokens(assets).await
+    }
+
     ///Returns user entitlements
     pub async fn user_entitlements(&mut self) -> Vec<Entitlement> {
         self.egs.user_entitlements().await.unwrap_or_else(|_| Vec::new())
     }
 
+    /// Returns the user entitlements keyed by catalog item id
+    ///
+    /// For items granted several times the most recent grant is kept.
+    pub async fn entitlements_map(&mut self) -> HashMap<String, Entitlement> {
+        Entitlement::map_by_catalog_item(self.user_entitlements().await)
+    }
+
     /// Returns the user library
     pub async fn library_items(&mut self, include_metadata: bool) -> Option<Library> {
-        match self.egs.library_items(include_metadata).await {
-            Ok(a) => Some(a),
-            Err(_) => None,
-        }
+        self.try_library_items(include_metadata).await.ok()
+    }
+
+    /// Returns the user library, propagating the error on failure
+    pub async fn try_library_items(
+        &mut self,
+        include_metadata: bool,
+    ) -> Result<Library, EpicAPIError> {
+        self.egs.library_items(include_metadata).await
+    }
+
+    /// Returns the user library as a stream of records
+    ///
+    /// Pages are fetched lazily while the stream is polled, so records can be processed as they arrive.
+    pub fn library_items_stream(
+        &self,
+        include_metadata: bool,
+    ) -> impl Stream<Item = Result<Record, EpicAPIError>> + '_ {
+        self.egs.library_items_stream(include_metadata)
+    }
+
+    /// Resolve a library record to the matching asset from the launcher assets listing
+    ///
+    /// Records lack the build version and asset id needed for downloading, the asset provides them.
+    pub async fn record_to_asset(
+        &mut self,
+        record: &Record,
+        platform: Option<String>,
+        label: Option<String>,
+    ) -> Option<EpicAsset> {
+        self.list_assets(platform, label).await.into_iter().find(|asset| {
+            asset.app_name == record.app_name
+                && asset.namespace == record.namespace
+                && asset.catalog_item_id == record.catalog_item_id
+        })
     }
 
-    /// Returns the user FAB library
+    /// Returns the user FAB library of Unreal Engine assets, with titles and descriptions in English
     pub async fn fab_library_items(
         &mut self,
         account_id: String,
     ) -> Option<api::types::fab_library::FabLibrary> {
-        match self.egs.fab_library_items(account_id).await {
-            Ok(a) => Some(a),
-            Err(_) => None,
-        }
+        self.egs
+            .fab_library_items(account_id, FabLibraryOptions::default())
+            .await
+            .ok()
+    }
+
+    /// Returns the user FAB library, localized for the `locale` (e.g. "de") where FAB supports it
+    ///
+    /// The locale is sent as the `Accept-Language` header.
+    pub async fn fab_library_items_localized(
+        &mut self,
+        account_id: String,
+        locale: &str,
+    ) -> Option<api::types::fab_library::FabLibrary> {
+        let options = FabLibraryOptions {
+            locale,
+            ..Default::default()
+        };
+        self.egs.fab_library_items(account_id, options).await.ok()
+    }
+
+    /// Returns the user FAB library for a distribution target other than Unreal Engine
+    ///
+    /// The target is the library path segment, `fab_library_items` uses `ue`.
+    pub async fn fab_library_items_for(
+        &mut self,
+        account_id: String,
+        distribution_target: &str,
+    ) -> Option<api::types::fab_library::FabLibrary> {
+        let options = FabLibraryOptions {
+            target: distribution_target,
+            ..Default::default()
+        };
+        self.egs.fab_library_items(account_id, options).await.ok()
+    }
+
+    /// Returns the user FAB library, fetched in pages of `count` assets
+    ///
+    /// Smaller pages answer faster, larger ones need fewer requests. The count has to be between
+    /// 1 and 100, the default, otherwise `None` is returned.
+    pub async fn fab_library_items_with_count(
+        &mut self,
+        account_id: String,
+        count: u32,
+    ) -> Option<api::types::fab_library::FabLibrary> {
+        let options = FabLibraryOptions {
+            count,
+            ..Default::default()
+        };
+        self.egs.fab_library_items(account_id, options).await.ok()
+    }
+
+    /// Returns a single page of the user FAB library with up to `count` assets
+    ///
+    /// Pass the next cursor of the previous page to continue. Fails with
+    /// `EpicAPIError::InvalidParams` when the count is not between 1 and 100.
+    pub async fn fab_library_page(
+        &self,
+        account_id: &str,
+        cursor: Option<String>,
+        count: u32,
+    ) -> Result<api::types::fab_library::FabLibrary, EpicAPIError> {
+        let options = FabLibraryOptions {
+            count,
+            ..Default::default()
+        };
+        self.egs.fab_library_page(account_id, cursor, options).await
+    }
+
+    /// Returns the user FAB library up to the first asset matching the predicate
+    ///
+    /// Stops fetching pages once a page contains a matching asset. Returns the found asset, if any,
+    /// and the library gathered so far, whose next cursor allows continuing from there.
+    /// When a page fails the error is returned instead of the asset, alongside the pages fetched
+    /// before it, and the next cursor points at the failed page.
+    pub async fn fab_library_items_until(
+        &mut self,
+        account_id: String,
+        predicate: impl Fn(&FabAsset) -> bool,
+    ) -> (
+        Result<Option<FabAsset>, EpicAPIError>,
+        api::types::fab_library::FabLibrary,
+    ) {
+        self.egs
+            .fab_library_items_until(account_id, predicate)
+            .await
+    }
+
+    /// Returns the user FAB library as a stream of assets
+    ///
+    /// Pages of 100 assets are fetched lazily while the stream is polled.
+    pub fn fab_library_stream(
+        &self,
+        account_id: String,
+    ) -> impl Stream<Item = Result<FabAsset, EpicAPIError>> + '_ {
+        self.egs.fab_library_stream(account_id)
     }
 
     /// Returns a DownloadManifest for a specified file manifest
     pub async fn asset_download_manifests(&self, manifest: AssetManifest) -> Vec<DownloadManifest> {
-        self.egs.asset_download_manifests(manifest).await
+        self.egs.asset_download_manifests(manifest, None).await
+    }
+
+    /// Returns a DownloadManifest for a specified file manifest, with a timeout for each download
+    ///
+    /// `None` uses the `download_timeout` of the builder.
+    pub async fn asset_download_manifests_with_timeout(
+        &self,
+        manifest: AssetManifest,
+        timeout: Option<Duration>,
+    ) -> Vec<DownloadManifest> {
+        self.egs.asset_download_manifests(manifest, timeout).await
+    }
+
+    /// Returns the download manifests of all assets, keyed by app name
+    ///
+    /// Lists the assets, resolves their asset manifests and fetches the download manifests,
+    /// with at most `concurrency` assets processed at once. Assets that failed to resolve are left out.
+    pub async fn download_manifests_for_all_assets(
+        &mut self,
+        platform: Option<String>,
+        label: Option<String>,
+        concurrency: usize,
+    ) -> HashMap<String, Vec<DownloadManifest>> {
+        self.egs
+            .download_manifests_for_all_assets(platform, label, concurrency)
+            .await
+            .unwrap_or_default()
     }
 
     /// Return a Download Manifest for specified FAB download and url
+    ///
+    /// When the download has a manifest hash the downloaded manifest must match it.
     pub async fn fab_download_manifest(
         &self,
         download_info: DownloadInfo,
@@ -267,4 +820,212 @@ impl EpicGames {
             .fab_download_manifest(download_info, distribution_point_url)
             .await
     }
+
+    /// Return a Download Manifest for specified FAB download from the distribution point with
+    /// the latest signature expiration
+    ///
+    /// The chosen distribution point is returned too, so callers can log it or retry with another.
+    pub async fn fab_download_manifest_auto(
+        &self,
+        download_info: DownloadInfo,
+    ) -> Result<(DownloadManifest, DistributionPoint), EpicAPIError> {
+        self.egs.fab_download_manifest_auto(download_info).await
+    }
+
+    /// Return a Download Manifest for specified FAB download, trying all distribution points
+    ///
+    /// Distribution points with expired signatures are skipped, the others are tried in order until
+    /// one returns a valid manifest matching the manifest hash.
+    /// Fails with `EpicAPIError::APIError` listing the failures.
+    pub async fn fab_download_manifest_with_fallback(
+        &self,
+        download_info: DownloadInfo,
+    ) -> Result<(DownloadManifest, DistributionPoint), EpicAPIError> {
+        self.egs
+            .fab_download_manifest_with_fallback(download_info)
+            .await
+    }
+
+    /// Download and parse a single chunk
+    pub async fn download_chunk(&self, link: &url::Url) -> Result<Chunk, EpicAPIError> {
+        self.egs.download_chunk(link, None).await
+    }
+
+    /// Download and parse a single chunk, failing when it takes longer than the timeout
+    ///
+    /// `None` uses the `download_timeout` of the builder.
+    pub async fn download_chunk_with_timeout(
+        &self,
+        link: &url::Url,
+        timeout: Option<Duration>,
+    ) -> Result<Chunk, EpicAPIError> {
+        self.egs.download_chunk(link, timeout).await
+    }
+
+    /// Download and parse a chunk, reporting the number of bytes received as the data arrives
+    ///
+    /// `progress` is called with the size of every received piece, not the running total.
+    pub async fn download_chunk_with_progress(
+        &self,
+        link: &url::Url,
+        progress: impl FnMut(u64),
+    ) -> Result<Chunk, EpicAPIError> {
+        self.egs
+            .download_chunk_with_progress(link, None, progress)
+            .await
+    }
+
+    /// Download an image, such as a `KeyImage` url, using the crate's HTTP client
+    pub async fn fetch_image(&self, url: &url::Url) -> Result<Vec<u8>, EpicAPIError> {
+        self.egs.fetch_image(url).await
+    }
+
+    /// Download a single file from the manifest into the target path
+    ///
+    /// The file must come from `DownloadManifest::files` of the given manifest so its chunk parts
+    /// carry download links.
+    /// Cancelling the token stops the download between chunks with `EpicAPIError::Cancelled`.
+    /// Chunks with a SHA in the manifest are verified and downloaded once more on a mismatch.
+    pub async fn download_file(
+        &self,
+        manifest: &DownloadManifest,
+        file: &FileManifestList,
+        target: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<(), EpicAPIError> {
+        self.egs.download_file(manifest, file, target, cancel).await
+    }
+
+    /// Download all files of a manifest into the target directory
+    ///
+    /// With `resume` files that are already present with the expected hash are not downloaded again.
+    /// `progress` is called after every finished file.
+    /// Cancelling the token stops the installation between chunks with `EpicAPIError::Cancelled`.
+    /// Chunks with a SHA in the manifest are verified and downloaded once more on a mismatch.
+    pub async fn install_asset(
+        &self,
+        manifest: &DownloadManifest,
+        target_dir: &Path,
+        resume: bool,
+        cancel: &CancellationToken,
+        progress: impl Fn(InstallProgress),
+    ) -> Result<(), EpicAPIError> {
+        self.egs
+            .install_asset(manifest, target_dir, resume, cancel, progress)
+            .await
+    }
+
+    /// Download only the files of a manifest selected by the install tags
+    ///
+    /// Files without install tags are always installed, tagged files only when one of their
+    /// tags is in `tags`. Otherwise behaves like `install_asset`.
+    pub async fn install_asset_selective(
+        &self,
+        manifest: &DownloadManifest,
+        target_dir: &Path,
+        tags: &[String],
+        resume: bool,
+        cancel: &CancellationToken,
+        progress: impl Fn(InstallProgress),
+    ) -> Result<(), EpicAPIError> {
+        self.egs
+            .install_asset_selective(manifest, target_dir, tags, resume, cancel, progress)
+            .await
+    }
+
+    /// Download all files of a manifest into the target directory in parallel
+    ///
+    /// Up to `file_concurrency` files are written and `chunk_concurrency` chunks downloaded at once.
+    /// Chunks used by several files are kept in a cache of `cache_capacity` bytes, so they are
+    /// downloaded only once. `progress` is called after every finished file with the aggregate
+    /// progress of the installation. With `resume` files that are already present with the
+    /// expected hash are skipped. Chunks are verified as in `install_asset`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn install_asset_concurrent(
+        &self,
+        manifest: &DownloadManifest,
+        target_dir: &Path,
+        file_concurrency: usize,
+        chunk_concurrency: usize,
+        cache_capacity: usize,
+        resume: bool,
+        cancel: &CancellationToken,
+        progress: impl Fn(InstallProgress),
+    ) -> Result<(), EpicAPIError> {
+        self.egs
+            .install_asset_concurrent(
+                manifest,
+                target_dir,
+                file_concurrency,
+                chunk_concurrency,
+                cache_capacity,
+                resume,
+                cancel,
+                progress,
+            )
+            .await
+    }
+
+    /// Verify the files of an installation against the manifest
+    ///
+    /// Every file is checked for existence, size and hash, the result is sorted by filename.
+    /// Files that exist but fail to be read, e.g. for lack of permissions, are reported as
+    /// `FileStatus::Unreadable` rather than missing or corrupt.
+    /// Fails with `EpicAPIError::InvalidParams` when a file path leaves the install directory.
+    pub async fn verify_installation(
+        &self,
+        manifest: &DownloadManifest,
+        install_dir: &Path,
+    ) -> Result<Vec<FileStatus>, EpicAPIError> {
+        api::download::verify_installation(manifest, install_dir).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::mock::{response, MockServer};
+    use crate::api::types::account::{AccountData, UserData};
+    use crate::api::{ClientConfig, EpicAPI};
+    use crate::EpicGames;
+
+    fn cached(egs: &EpicGames) -> bool {
+        egs.account_cache.lock().unwrap().is_some()
+    }
+
+    #[tokio::test]
+    async fn session_changes_clear_account_cache() {
+        let token = MockServer::start(vec![response(
+            200,
+            &[("Content-Type", "application/json")],
+            r#"{"access_token":"token","account_id":"other"}"#,
+        )])
+        .await;
+        let mut egs = EpicGames {
+            egs: EpicAPI::with_config(ClientConfig {
+                token_url: Some(token.url.clone()),
+                ..Default::default()
+            }),
+            account_cache: Default::default(),
+        };
+        let fill = |egs: &EpicGames| {
+            *egs.account_cache.lock().unwrap() = Some(AccountData::default());
+        };
+
+        fill(&egs);
+        egs.set_user_details(UserData::default());
+        assert!(!cached(&egs));
+
+        fill(&egs);
+        assert!(egs.auth_code(None, Some("code".to_string())).await);
+        assert!(!cached(&egs));
+
+        fill(&egs);
+        assert!(!egs.login().await);
+        assert!(!cached(&egs));
+
+        fill(&egs);
+        let clone = egs.clone();
+        egs.invalidate_account_cache();
+        assert!(!cached(&clone));
+    }
 }